@@ -1,6 +1,6 @@
 use dioxus::prelude::*;
 use theme::dioxus::ThemeProvider;
-use theme::dioxus::use_theme;
+use theme::dioxus::{use_theme, use_theme_tokens};
 use theme::Theme;
 
 #[derive(Debug, Clone, Routable, PartialEq)]
@@ -40,10 +40,10 @@ fn ThemeToggle() -> Element {
 
     let onclick = {
         move |_| {
-            let new_theme = match (theme_ctx.theme)() {
-                Theme::Light => Theme::Dark,
-                Theme::Dark => Theme::Light,
-                _ => Theme::Light,
+            let new_theme = if (theme_ctx.resolved_theme)().is_dark(None) {
+                Theme::Light
+            } else {
+                Theme::Dark
             };
             theme_ctx.set_theme.call(new_theme);
         }
@@ -73,8 +73,7 @@ fn ThemeToggle() -> Element {
 
 #[component]
 fn Product() -> Element {
-    let theme_ctx = use_theme();
-    let colors = (theme_ctx.resolved_theme)().colors(Some(&(theme_ctx.custom_themes)()));
+    let colors = use_theme_tokens()();
 
     let mut products = vec![];
 
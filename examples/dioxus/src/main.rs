@@ -3,8 +3,9 @@ use dioxus_logger::tracing;
 use maplit::hashmap;
 use table_rs::dioxus::table::Table;
 use table_rs::dioxus::types::{Column, TableClasses};
-use theme::dioxus::{use_theme, ThemeProvider};
-use theme::Theme;
+use theme::dioxus::{
+    use_theme_tokens, ThemeCycleButton, ThemeProvider, ThemeSelect, ThemeToggle,
+};
 
 const FAVICON: Asset = asset!("/assets/favicon.ico");
 // const MAIN_CSS: Asset = asset!("/assets/styles.css");
@@ -30,37 +31,19 @@ fn app() -> Element {
 }
 
 #[component]
-fn ThemeToggle() -> Element {
-    let theme_ctx = use_theme();
-
-    let onclick = {
-        move |_| {
-            let new_theme = match (theme_ctx.theme)() {
-                Theme::Light => Theme::Dark,
-                Theme::Dark => Theme::Light,
-                _ => Theme::Light,
-            };
-            theme_ctx.set_theme.call(new_theme);
-        }
-    };
-
+fn ThemeControls() -> Element {
     rsx! {
-        div { class: "flex items-center justify-center",
-            button {
-                onclick: onclick,
-                class: "relative w-[50px] h-[26px] rounded-full bg-gray-300 dark:bg-gray-800 p-1 flex items-center justify-between transition-colors duration-300",
-                span {
-                    class: "absolute top-[2px] left-[2px] w-[22px] h-[22px] rounded-full bg-white transition-transform duration-300 transform translate-x-0 dark:translate-x-[24px]"
-                }
-                span {
-                    class: "absolute inset-0 flex items-center justify-between px-2 text-xs z-0",
-                    i {
-                        class: "fas fa-moon text-yellow-400 dark:opacity-100 opacity-0 transition-opacity duration-300"
-                    }
-                    i {
-                        class: "fas fa-sun text-yellow-600 dark:opacity-0 opacity-100 transition-opacity duration-300"
-                    }
-                }
+        div { class: "flex items-center justify-center gap-4",
+            ThemeToggle {
+                class: "inline-flex rounded-full bg-gray-200 dark:bg-gray-800 p-1",
+                button_class: "px-3 py-1 rounded-full text-sm text-gray-700 dark:text-gray-200",
+                active_class: "bg-white dark:bg-gray-600 shadow",
+            }
+            ThemeCycleButton {
+                class: "px-3 py-1 rounded-full text-sm bg-gray-200 dark:bg-gray-800 text-gray-700 dark:text-gray-200",
+            }
+            ThemeSelect {
+                class: "px-2 py-1 rounded text-sm bg-gray-200 dark:bg-gray-800 text-gray-700 dark:text-gray-200",
             }
         }
     }
@@ -68,8 +51,7 @@ fn ThemeToggle() -> Element {
 
 #[component]
 fn Product() -> Element {
-    let theme_ctx = use_theme();
-    let colors = (theme_ctx.resolved_theme)().colors(Some(&(theme_ctx.custom_themes)()));
+    let colors = use_theme_tokens()();
 
     let mut products = vec![];
 
@@ -105,7 +87,7 @@ fn Product() -> Element {
     }
 
     rsx! {
-        ThemeToggle {}
+        ThemeControls {}
         div {
             class: "mt-4 grid grid-cols-1 md:grid-cols-2 gap-6 p-6",
             style: "background: {colors.background}",
@@ -165,7 +147,7 @@ fn AdminTable() -> Element {
     };
 
     rsx! {
-        ThemeToggle {}
+        ThemeControls {}
         Table {
             data: data,
             columns: columns,
@@ -194,13 +176,12 @@ fn Examples() -> Element {
                     pre {
                         class: "font-mono text-xs text-white p-4 bg-gray-800 mb-8 rounded-md w-full overflow-x-auto",
                         r#"use dioxus::prelude::*;
-use theme::dioxus::use_theme;
+use theme::dioxus::use_theme_tokens;
 use theme::Theme;
 
 #[component]
 fn Product() -> Element {{
-    let theme_ctx = use_theme();
-    let colors = (theme_ctx.resolved_theme)().colors(Some(&(theme_ctx.custom_themes)()));
+    let colors = use_theme_tokens()();
 
     let mut products = vec![];
 
@@ -236,7 +217,7 @@ fn Product() -> Element {{
     }}
 
     rsx! {{
-        ThemeToggle {{}}
+        ThemeControls {{}}
         div {{
             class: "mt-4 grid grid-cols-1 md:grid-cols-2 gap-6 p-6",
             style: "background: {{colors.background}}",
@@ -287,7 +268,7 @@ fn AdminTable() -> Element {{
     }};
 
     rsx! {{
-        ThemeToggle {{}}
+        ThemeControls {{}}
         Table {{
             data: data,
             columns: columns,
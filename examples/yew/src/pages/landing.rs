@@ -4,60 +4,37 @@ use sidebar::yew::menu::Menu;
 use sidebar::yew::sidebar::Sidebar;
 use table_rs::yew::table::Table;
 use table_rs::yew::types::{Column, TableClasses};
-use theme::yew::use_theme;
-use theme::Theme;
+use theme::yew::{use_theme_tokens, ThemeCycleButton, ThemeSelect, ThemeToggle};
 use yew::prelude::*;
 
-#[function_component(ThemeToggle)]
-pub fn theme_toggle() -> Html {
-    let theme_ctx = use_theme();
-
-    let onclick = {
-        let theme_ctx = theme_ctx.clone();
-        Callback::from(move |_| {
-            let new_theme = match *theme_ctx.theme {
-                Theme::Light => Theme::Dark,
-                Theme::Dark => Theme::Light,
-                _ => Theme::Light,
-            };
-            theme_ctx.set_theme.emit(new_theme);
-        })
-    };
-
+#[function_component(ThemeControls)]
+pub fn theme_controls() -> Html {
     html! {
-        <div class="flex items-center justify-center">
-            <button
-                {onclick}
-                class="relative w-[50px] h-[26px] rounded-full bg-gray-300 dark:bg-gray-800 p-1 flex items-center justify-between transition-colors duration-300"
-            >
-                <span
-                    class="absolute top-[2px] left-[2px] w-[22px] h-[22px] rounded-full bg-white transition-transform duration-300 transform translate-x-0 dark:translate-x-[24px]"
-                />
-                <span class="absolute inset-0 flex items-center justify-between px-2 text-xs z-0">
-                    <i
-                        class="fas fa-moon text-yellow-400 dark:opacity-100 opacity-0 transition-opacity duration-300"
-                    />
-                    <i
-                        class="fas fa-sun text-yellow-600 dark:opacity-0 opacity-100 transition-opacity duration-300"
-                    />
-                </span>
-            </button>
+        <div class="flex items-center justify-center gap-4">
+            <ThemeToggle
+                class="inline-flex rounded-full bg-gray-200 dark:bg-gray-800 p-1"
+                button_class="px-3 py-1 rounded-full text-sm text-gray-700 dark:text-gray-200"
+                active_class="bg-white dark:bg-gray-600 shadow"
+            />
+            <ThemeCycleButton
+                class="px-3 py-1 rounded-full text-sm bg-gray-200 dark:bg-gray-800 text-gray-700 dark:text-gray-200"
+            />
+            <ThemeSelect
+                class="px-2 py-1 rounded text-sm bg-gray-200 dark:bg-gray-800 text-gray-700 dark:text-gray-200"
+            />
         </div>
     }
 }
 
 #[function_component(Dashboard)]
 pub fn dashboard() -> Html {
-    let theme_ctx = use_theme();
     let selected = use_state(|| "Dashboard".to_string());
 
-    let colors = theme_ctx
-        .resolved_theme
-        .colors(Some(&*theme_ctx.custom_themes));
+    let colors = use_theme_tokens();
 
     html! {
         <>
-            <ThemeToggle />
+            <ThemeControls />
             <div
                 class="mt-4 grid grid-cols-1 md:grid-cols-2 gap-4 p-4 bg-gray-100 dark:bg-gray-900 transition-colors duration-300"
                 style={format!("background: {}", colors.background)}
@@ -128,13 +105,10 @@ pub fn dashboard() -> Html {
 
 #[function_component(Product)]
 pub fn product() -> Html {
-    let theme_ctx = use_theme();
-    let colors = theme_ctx
-        .resolved_theme
-        .colors(Some(&*theme_ctx.custom_themes));
+    let colors = use_theme_tokens();
     html! {
         <>
-            <ThemeToggle />
+            <ThemeControls />
             <div
                 class="mt-4 grid grid-cols-1 md:grid-cols-2 gap-6 p-6"
                 style={format!("background: {}", colors.background)}
@@ -206,7 +180,7 @@ pub fn admin_table() -> Html {
 
     html! {
         <>
-            <ThemeToggle />
+            <ThemeControls />
             <Table data={data} columns={columns} classes={classes} paginate=false search=false />
         </>
     }
@@ -229,17 +203,14 @@ pub fn landing_page() -> Html {
 use sidebar::yew::item::MenuItem;
 use sidebar::yew::menu::Menu;
 use sidebar::yew::sidebar::Sidebar;
-use theme::yew::use_theme;
+use theme::yew::use_theme_tokens;
 
 
 #[function_component(Dashboard)]
 pub fn dashboard() -> Html {
-    let theme_ctx = use_theme();
     let selected = use_state(|| "Dashboard".to_string());
 
-    let colors = theme_ctx
-        .resolved_theme
-        .colors(Some(&*theme_ctx.custom_themes));
+    let colors = use_theme_tokens();
 
     html! {
         <div
@@ -315,16 +286,13 @@ pub fn dashboard() -> Html {
                         class="font-mono text-xs text-white p-4 bg-gray-800 mb-8 rounded-md w-full overflow-x-auto"
                     >
                         { r#"use yew::prelude::*;
-use theme::yew::use_theme;
+use theme::yew::use_theme_tokens;
 use theme::Theme;
 
 
 #[function_component(Product)]
 pub fn product() -> Html {
-    let theme_ctx = use_theme();
-    let colors = theme_ctx
-        .resolved_theme
-        .colors(Some(&*theme_ctx.custom_themes));
+    let colors = use_theme_tokens();
     html! {
         <div
             class="grid grid-cols-1 md:grid-cols-2 gap-6 p-6"
@@ -0,0 +1,163 @@
+//! Independent theming axes, such as density or accent, managed alongside the color scheme.
+//!
+//! Each axis keeps its own value, storage key and optional forced value, and is written to the
+//! root element as a `data-<name>` attribute.
+//!
+//! ```
+//! use theme::ThemeAxis;
+//!
+//! let density = ThemeAxis::density().with_default("compact");
+//! assert_eq!(density.attribute(), "data-density");
+//! assert_eq!(density.resolve(Some("spacious")), "spacious");
+//! assert_eq!(density.resolve(Some("huge")), "compact");
+//! ```
+
+/// Name of the built-in density axis.
+pub const DENSITY_AXIS: &str = "density";
+/// Name of the built-in contrast axis, which overrides the `prefers-contrast` preference.
+pub const CONTRAST_AXIS: &str = "contrast";
+/// Name of the built-in accent axis, whose hex color replaces the `primary` token.
+pub const ACCENT_AXIS: &str = "accent";
+
+/// A theming dimension independent of the color scheme.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeAxis {
+    /// Name of the axis, also used for its `data-<name>` root attribute.
+    pub name: String,
+    /// Values the axis accepts. An empty list accepts any value.
+    pub values: Vec<String>,
+    /// Value used when nothing valid is stored.
+    pub default: String,
+    /// Storage key of the axis, `theme-<name>` when unset.
+    pub storage_key: Option<String>,
+    /// Value that overrides both the stored and the default value.
+    pub forced: Option<String>,
+}
+
+impl ThemeAxis {
+    /// Creates an axis accepting any value.
+    pub fn new(name: impl Into<String>, default: impl Into<String>) -> Self {
+        ThemeAxis {
+            name: name.into(),
+            values: Vec::new(),
+            default: default.into(),
+            storage_key: None,
+            forced: None,
+        }
+    }
+
+    /// The `density` axis: `comfortable` (default), `compact` or `spacious`.
+    pub fn density() -> Self {
+        ThemeAxis::new(DENSITY_AXIS, "comfortable").with_values([
+            "comfortable",
+            "compact",
+            "spacious",
+        ])
+    }
+
+    /// The `contrast` axis: `system` (default) follows `prefers-contrast`, while `no-preference`,
+    /// `more` and `less` override it.
+    pub fn contrast() -> Self {
+        ThemeAxis::new(CONTRAST_AXIS, "system").with_values([
+            "system",
+            "no-preference",
+            "more",
+            "less",
+        ])
+    }
+
+    /// The `accent` axis: a hex color replacing the `primary` token, or empty (default) to keep
+    /// the theme's own.
+    pub fn accent() -> Self {
+        ThemeAxis::new(ACCENT_AXIS, "")
+    }
+
+    /// Restricts the axis to `values`.
+    pub fn with_values<I, S>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.values = values.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the value used when nothing valid is stored.
+    pub fn with_default(mut self, default: impl Into<String>) -> Self {
+        self.default = default.into();
+        self
+    }
+
+    /// Persists the axis under `key` instead of `theme-<name>`.
+    pub fn with_storage_key(mut self, key: impl Into<String>) -> Self {
+        self.storage_key = Some(key.into());
+        self
+    }
+
+    /// Locks the axis to `value`.
+    pub fn forced(mut self, value: impl Into<String>) -> Self {
+        self.forced = Some(value.into());
+        self
+    }
+
+    /// Returns the root attribute the axis is written to, e.g. `data-density`.
+    pub fn attribute(&self) -> String {
+        format!("data-{}", self.name)
+    }
+
+    /// Returns the key the axis value is persisted under.
+    pub fn storage_key(&self) -> String {
+        self.storage_key
+            .clone()
+            .unwrap_or_else(|| format!("theme-{}", self.name))
+    }
+
+    /// Returns `true` if `value` is one of the axis values.
+    pub fn accepts(&self, value: &str) -> bool {
+        self.values.is_empty() || self.values.iter().any(|v| v == value)
+    }
+
+    /// Returns the value in effect: the forced value, then `stored` if accepted, then the
+    /// default.
+    pub fn resolve(&self, stored: Option<&str>) -> String {
+        self.forced
+            .as_deref()
+            .or(stored.filter(|value| self.accepts(value)))
+            .unwrap_or(&self.default)
+            .to_string()
+    }
+}
+
+/// Resolves the value of every axis, reading stored values through `stored`.
+#[cfg(any(feature = "yew", feature = "dio"))]
+pub(crate) fn initial_values(
+    axes: &[ThemeAxis],
+    stored: impl Fn(&str) -> Option<String>,
+) -> std::collections::HashMap<String, String> {
+    axes.iter()
+        .map(|axis| {
+            let value = axis.resolve(stored(&axis.storage_key()).as_deref());
+            (axis.name.clone(), value)
+        })
+        .collect()
+}
+
+/// Returns the contrast selected on the contrast axis, if it overrides the media preference.
+#[cfg(all(target_arch = "wasm32", any(feature = "yew", feature = "dio")))]
+pub(crate) fn contrast_override(
+    values: &std::collections::HashMap<String, String>,
+) -> Option<crate::Contrast> {
+    values.get(CONTRAST_AXIS)?.parse().ok()
+}
+
+/// Applies the accent axis, if any, to `tokens`.
+#[cfg(any(feature = "yew", feature = "dio"))]
+pub(crate) fn apply_accent(
+    tokens: crate::ColorTokens,
+    values: &std::collections::HashMap<String, String>,
+) -> crate::ColorTokens {
+    match values.get(ACCENT_AXIS) {
+        Some(accent) => tokens.with_accent(accent),
+        None => tokens,
+    }
+}
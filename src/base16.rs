@@ -0,0 +1,478 @@
+//! Import and export of [Base16](https://github.com/tinted-theming/home) and Base24 color schemes.
+//!
+//! Base16 schemes describe a palette with sixteen slots (`base00` to `base0F`), Base24 adds eight
+//! more (`base10` to `base17`). Both the legacy format (`scheme: "..."` with bare hex values) and the
+//! tinted-theming format (`name: "..."` with a nested `palette:` block) are understood.
+//!
+//! When importing, slots are mapped onto [`ColorTokens`] as follows:
+//!
+//! | Slot     | Token        |
+//! | -------- | ------------ |
+//! | `base00` | `background` |
+//! | `base05` | `text`       |
+//! | `base0D` | `primary`    |
+//! | `base0E` | `secondary`  |
+//! | `base08` | `error`      |
+//! | `base0A` | `warning`    |
+//! | `base0B` | `success`    |
+//!
+//! The Base24 slots carry no extra tokens and are kept on the [`Base16Scheme`] only.
+//!
+//! When exporting, the same mapping is used in reverse. `base01` to `base04` are interpolated
+//! between `background` and `text`, `base06` and `base07` continue from `text` towards white
+//! (light text) or black (dark text). `base09` and `base0F` reuse `warning` and `error`,
+//! `base0C` reuses `secondary`, and missing optional tokens fall back to `primary`.
+
+use crate::common::{ColorTokens, CustomTheme, format_hex, mix_rgb, parse_hex};
+use crate::error::ThemeError;
+
+const BASE16_SLOTS: usize = 16;
+const BASE24_SLOTS: usize = 24;
+
+/// The palette system a [`Base16Scheme`] was declared with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Base16System {
+    /// Sixteen slots, `base00` to `base0F`.
+    #[default]
+    Base16,
+    /// Twenty-four slots, `base00` to `base17`.
+    Base24,
+}
+
+/// A parsed Base16 or Base24 color scheme.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Base16Scheme {
+    /// Whether the scheme has 16 or 24 slots.
+    pub system: Base16System,
+    /// Human readable scheme name, e.g. `"Solarized Dark"`.
+    pub name: String,
+    /// Scheme author, empty if unknown.
+    pub author: String,
+    /// Optional `light` / `dark` variant hint.
+    pub variant: Option<String>,
+    /// Palette slots as `#rrggbb` colors, indexed by slot number (`base0A` is `slots[10]`).
+    pub slots: Vec<String>,
+}
+
+impl Base16Scheme {
+    /// Parses a Base16 or Base24 YAML document.
+    pub fn parse(yaml: &str) -> Result<Self, ThemeError> {
+        let mut name = None;
+        let mut author = String::new();
+        let mut variant = None;
+        let mut declared_system = None;
+        let mut slots: Vec<Option<String>> = vec![None; BASE24_SLOTS];
+
+        for line in yaml.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim();
+            let value = unquote(value);
+            if value.is_empty() {
+                continue;
+            }
+
+            match key {
+                "scheme" | "name" => name = Some(value.to_string()),
+                "author" => author = value.to_string(),
+                "variant" => variant = Some(value.to_string()),
+                "system" => {
+                    declared_system = Some(match value {
+                        "base16" => Base16System::Base16,
+                        "base24" => Base16System::Base24,
+                        other => {
+                            return Err(parse_error(format!(
+                                "Unsupported scheme system '{}'.",
+                                other
+                            )));
+                        }
+                    })
+                }
+                _ => {
+                    if let Some(index) = slot_index(key) {
+                        let rgb = parse_hex(value).ok_or_else(|| ThemeError::InvalidColor {
+                            field: key.to_string(),
+                            value: value.to_string(),
+                        })?;
+                        slots[index] = Some(format_hex(rgb));
+                    }
+                }
+            }
+        }
+
+        let has_base24_slots = slots[BASE16_SLOTS..].iter().any(Option::is_some);
+        let system = declared_system.unwrap_or(if has_base24_slots {
+            Base16System::Base24
+        } else {
+            Base16System::Base16
+        });
+        let len = match system {
+            Base16System::Base16 => BASE16_SLOTS,
+            Base16System::Base24 => BASE24_SLOTS,
+        };
+
+        let slots = slots
+            .into_iter()
+            .take(len)
+            .enumerate()
+            .map(|(i, slot)| {
+                slot.ok_or_else(|| parse_error(format!("Missing slot '{}'.", slot_name(i))))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Base16Scheme {
+            system,
+            name: name.ok_or_else(|| parse_error("Missing scheme name.".to_string()))?,
+            author,
+            variant,
+            slots,
+        })
+    }
+
+    /// Builds a Base16 scheme from a custom theme using the export mapping described in the
+    /// [module documentation](self).
+    ///
+    /// Fails with [`ThemeError::InvalidColor`] for the first token that is not a hex color.
+    pub fn from_theme(theme: &CustomTheme) -> Result<Self, ThemeError> {
+        let tokens = &theme.tokens;
+        let rgb = |field: &str, color: &str| {
+            parse_hex(color).ok_or_else(|| ThemeError::InvalidColor {
+                field: field.to_string(),
+                value: color.to_string(),
+            })
+        };
+        let optional = |field: &str, color: Option<&str>, fallback: [u8; 3]| {
+            color.map_or(Ok(fallback), |color| rgb(field, color))
+        };
+
+        let background = rgb("background", &tokens.background)?;
+        let text = rgb("text", &tokens.text)?;
+        let primary = rgb("primary", &tokens.primary)?;
+        let secondary = rgb("secondary", &tokens.secondary)?;
+        let error = optional("error", tokens.error.as_deref(), primary)?;
+        let warning = optional("warning", tokens.warning.as_deref(), primary)?;
+        let success = optional("success", tokens.success.as_deref(), primary)?;
+
+        let pole = if luma(text) >= luma(background) {
+            [0xff, 0xff, 0xff]
+        } else {
+            [0x00, 0x00, 0x00]
+        };
+
+        let slots = [
+            background,
+            mix_rgb(background, text, 0.1),
+            mix_rgb(background, text, 0.2),
+            mix_rgb(background, text, 0.4),
+            mix_rgb(background, text, 0.6),
+            text,
+            mix_rgb(text, pole, 0.5),
+            pole,
+            error,
+            warning,
+            warning,
+            success,
+            secondary,
+            primary,
+            secondary,
+            error,
+        ]
+        .into_iter()
+        .map(format_hex)
+        .collect();
+
+        Ok(Base16Scheme {
+            system: Base16System::Base16,
+            name: theme.name.clone(),
+            author: String::new(),
+            variant: Some(
+                if pole == [0xff, 0xff, 0xff] {
+                    "dark"
+                } else {
+                    "light"
+                }
+                .to_string(),
+            ),
+            slots,
+        })
+    }
+
+    /// Returns the color tokens for this scheme.
+    pub fn tokens(&self) -> ColorTokens {
+        ColorTokens {
+            primary: self.slots[0x0D].clone(),
+            secondary: self.slots[0x0E].clone(),
+            background: self.slots[0x00].clone(),
+            text: self.slots[0x05].clone(),
+            error: Some(self.slots[0x08].clone()),
+            warning: Some(self.slots[0x0A].clone()),
+            success: Some(self.slots[0x0B].clone()),
+        }
+    }
+
+    /// Converts the scheme into a custom theme.
+    ///
+    /// The theme name is the scheme name in kebab case, e.g. `"Solarized Dark"` becomes
+    /// `"solarized-dark"`, so it can be used as a `data-theme` value.
+    pub fn to_custom_theme(&self) -> CustomTheme {
+        CustomTheme {
+            name: slugify(&self.name),
+            tokens: self.tokens(),
+            base: None,
+        }
+    }
+
+    /// Serializes the scheme as legacy Base16/Base24 YAML, with bare lowercase hex values.
+    pub fn to_yaml(&self) -> String {
+        let mut yaml = format!(
+            "scheme: \"{}\"\nauthor: \"{}\"\n",
+            escape(&self.name),
+            escape(&self.author)
+        );
+        if let Some(variant) = &self.variant {
+            yaml.push_str(&format!("variant: \"{}\"\n", escape(variant)));
+        }
+        for (i, slot) in self.slots.iter().enumerate() {
+            yaml.push_str(&format!(
+                "{}: \"{}\"\n",
+                slot_name(i),
+                slot.trim_start_matches('#')
+            ));
+        }
+        yaml
+    }
+}
+
+impl CustomTheme {
+    /// Loads a Base16 or Base24 YAML scheme as a custom theme.
+    ///
+    /// See the [`base16`](crate::base16) module for the slot mapping.
+    pub fn from_base16(yaml: &str) -> Result<CustomTheme, ThemeError> {
+        Base16Scheme::parse(yaml).map(|scheme| scheme.to_custom_theme())
+    }
+
+    /// Exports this theme as Base16 YAML, failing if a token is not a hex color.
+    ///
+    /// See the [`base16`](crate::base16) module for the slot mapping.
+    pub fn to_base16(&self) -> Result<String, ThemeError> {
+        Base16Scheme::from_theme(self).map(|scheme| scheme.to_yaml())
+    }
+}
+
+fn parse_error(message: String) -> ThemeError {
+    ThemeError::Parse {
+        format: "Base16",
+        message,
+    }
+}
+
+fn slot_index(key: &str) -> Option<usize> {
+    let hex = key.strip_prefix("base")?;
+    if hex.len() != 2 {
+        return None;
+    }
+    let index = usize::from_str_radix(hex, 16).ok()?;
+    (index < BASE24_SLOTS).then_some(index)
+}
+
+fn slot_name(index: usize) -> String {
+    format!("base{:02X}", index)
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.find(quote).map(|end| &rest[..end]))
+        {
+            return inner;
+        }
+    }
+    match value.find(" #") {
+        Some(comment) => value[..comment].trim_end(),
+        None => value,
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn slugify(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn luma([r, g, b]: [u8; 3]) -> u32 {
+    299 * r as u32 + 587 * g as u32 + 114 * b as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::custom;
+
+    const LEGACY: &str = r#"
+# Tomorrow Night
+scheme: "Tomorrow Night"
+author: "Chris Kempson"
+base00: "1d1f21"
+base01: "282a2e"
+base02: "373b41"
+base03: "969896"
+base04: "b4b7b4"
+base05: "c5c8c6"
+base06: "e0e0e0"
+base07: "ffffff"
+base08: "cc6666"
+base09: "de935f"
+base0A: "f0c674"
+base0B: "b5bd68"
+base0C: "8abeb7"
+base0D: "81a2be"
+base0E: "b294bb"
+base0F: "a3685a"
+"#;
+
+    /// The tinted-theming format, mixing quoted and unquoted values.
+    const PALETTE: &str = r##"
+system: "base16"
+name: 'Paper'
+author: Someone # comment
+variant: light
+palette:
+  base00: "#f2eede"
+  base01: '#e3dcc4'
+  base02: "#d4c9a8"
+  base03: "#a89f84"
+  base04: "#7f7962"
+  base05: "#3c3836"
+  base06: "#282828"
+  base07: "#1d2021"
+  base08: "#cc241d"
+  base09: "#d65d0e"
+  base0A: "#d79921"
+  base0B: "#98971a"
+  base0C: "#689d6a"
+  base0D: "#458588"
+  base0E: "#b16286"
+  base0F: #9d0006 # trailing comment
+"##;
+
+    #[test]
+    fn parses_the_legacy_format() {
+        let scheme = Base16Scheme::parse(LEGACY).unwrap();
+        assert_eq!(scheme.system, Base16System::Base16);
+        assert_eq!(scheme.name, "Tomorrow Night");
+        assert_eq!(scheme.author, "Chris Kempson");
+        assert_eq!(scheme.variant, None);
+        assert_eq!(scheme.slots.len(), 16);
+        assert_eq!(scheme.slots[0x0A], "#f0c674");
+
+        let theme = scheme.to_custom_theme();
+        assert_eq!(theme.name, "tomorrow-night");
+        assert_eq!(theme.tokens.primary, "#81a2be");
+        assert_eq!(theme.tokens.text, "#c5c8c6");
+    }
+
+    #[test]
+    fn parses_the_palette_format_with_quoted_and_unquoted_values() {
+        let scheme = Base16Scheme::parse(PALETTE).unwrap();
+        assert_eq!(scheme.name, "Paper");
+        assert_eq!(scheme.author, "Someone");
+        assert_eq!(scheme.variant.as_deref(), Some("light"));
+        assert_eq!(scheme.slots[0x00], "#f2eede");
+        assert_eq!(scheme.slots[0x01], "#e3dcc4");
+        assert_eq!(scheme.slots[0x0F], "#9d0006");
+    }
+
+    #[test]
+    fn parses_base24_slots() {
+        let base24: String = (0x10..0x18)
+            .map(|slot| format!("base{:02X}: \"{:02x}0000\"\n", slot, slot))
+            .collect();
+
+        let detected = Base16Scheme::parse(&format!("{}{}", LEGACY, base24)).unwrap();
+        assert_eq!(detected.system, Base16System::Base24);
+        assert_eq!(detected.slots.len(), 24);
+        assert_eq!(detected.slots[0x17], "#170000");
+
+        let declared = PALETTE.replace("\"base16\"", "\"base24\"");
+        assert_eq!(
+            Base16Scheme::parse(&declared),
+            Err(parse_error("Missing slot 'base10'.".to_string()))
+        );
+        let indented: String = base24.lines().map(|line| format!("  {}\n", line)).collect();
+        assert_eq!(
+            Base16Scheme::parse(&format!("{}{}", declared, indented))
+                .unwrap()
+                .slots
+                .len(),
+            24
+        );
+    }
+
+    #[test]
+    fn reports_missing_slots_and_bad_colors() {
+        let missing = LEGACY.replace("base0D: \"81a2be\"\n", "");
+        assert_eq!(
+            Base16Scheme::parse(&missing),
+            Err(parse_error("Missing slot 'base0D'.".to_string()))
+        );
+
+        let invalid = LEGACY.replace("\"81a2be\"", "\"blue\"");
+        assert_eq!(
+            Base16Scheme::parse(&invalid),
+            Err(ThemeError::InvalidColor {
+                field: "base0D".to_string(),
+                value: "blue".to_string(),
+            })
+        );
+
+        let unnamed = LEGACY.replace("scheme: \"Tomorrow Night\"\n", "");
+        assert_eq!(
+            Base16Scheme::parse(&unnamed),
+            Err(parse_error("Missing scheme name.".to_string()))
+        );
+    }
+
+    #[test]
+    fn round_trips_through_export() {
+        let theme = CustomTheme::from_base16(LEGACY).unwrap();
+        let exported = theme.to_base16().unwrap();
+        let reimported = CustomTheme::from_base16(&exported).unwrap();
+        assert_eq!(reimported, theme);
+    }
+
+    #[test]
+    fn export_rejects_invalid_tokens() {
+        let mut theme = (*custom("broken", None, "#000001")).clone();
+        theme.tokens.warning = Some("orange".to_string());
+        assert_eq!(
+            theme.to_base16(),
+            Err(ThemeError::InvalidColor {
+                field: "warning".to_string(),
+                value: "orange".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn slot_names_are_case_insensitive_hex() {
+        assert_eq!(slot_index("base0a"), Some(10));
+        assert_eq!(slot_index("base17"), Some(23));
+        assert_eq!(slot_index("base18"), None);
+        assert_eq!(slot_index("base1"), None);
+        assert_eq!(unquote(" 'ab' # note"), "ab");
+        assert_eq!(unquote(" ab # note"), "ab");
+    }
+}
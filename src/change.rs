@@ -0,0 +1,108 @@
+//! Events describing a change of theme.
+//!
+//! Providers call their `on_change` callback with a [`ThemeChange`] once for each change of the
+//! selected or resolved theme, so apps can log analytics, sync the choice to a backend or
+//! redraw a canvas.
+//!
+//! ```
+//! use theme::{ChangeReason, Theme, ThemeChange};
+//!
+//! let change = ThemeChange {
+//!     previous: Theme::System,
+//!     next: Theme::System,
+//!     previous_resolved: Theme::Light,
+//!     next_resolved: Theme::Dark,
+//!     reason: ChangeReason::System,
+//! };
+//! assert!(change.is_effective());
+//! assert!(!change.is_selection());
+//! ```
+
+use crate::common::Theme;
+
+/// Why a provider's theme changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeReason {
+    /// The user picked a theme, committed a preview or changed an axis.
+    User,
+    /// The operating system's preferences or the enclosing provider changed.
+    System,
+    /// Another tab stored a theme, or the provider moved to another storage key.
+    Storage,
+    /// The hour crossed a boundary of a `ThemeResolver::Schedule`.
+    Schedule,
+    /// A preview started or ended.
+    Preview,
+    /// The `forced_theme` prop changed.
+    Forced,
+    /// The route's theme changed.
+    Route,
+    /// The `ThemePolicy` changed.
+    Policy,
+    /// A custom theme was added, replaced or restored.
+    CustomTheme,
+}
+
+impl ChangeReason {
+    /// Returns the lowercase name of the reason, e.g. `storage`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeReason::User => "user",
+            ChangeReason::System => "system",
+            ChangeReason::Storage => "storage",
+            ChangeReason::Schedule => "schedule",
+            ChangeReason::Preview => "preview",
+            ChangeReason::Forced => "forced",
+            ChangeReason::Route => "route",
+            ChangeReason::Policy => "policy",
+            ChangeReason::CustomTheme => "custom-theme",
+        }
+    }
+}
+
+/// A change of the selected or resolved theme.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeChange {
+    /// The theme selected before the change.
+    pub previous: Theme,
+    /// The theme selected after the change.
+    pub next: Theme,
+    /// The theme applied before the change.
+    pub previous_resolved: Theme,
+    /// The theme applied after the change.
+    pub next_resolved: Theme,
+    /// What caused the change.
+    pub reason: ChangeReason,
+}
+
+impl ThemeChange {
+    /// Returns whether the selected or the resolved theme differs.
+    pub fn is_effective(&self) -> bool {
+        self.previous != self.next || self.previous_resolved != self.next_resolved
+    }
+
+    /// Returns whether the selected theme changed, rather than only the resolved one.
+    pub fn is_selection(&self) -> bool {
+        self.previous != self.next
+    }
+}
+
+/// Returns the change from the `previous` selected and resolved themes, if both are known and
+/// either differs.
+#[cfg(any(feature = "yew", feature = "dio"))]
+pub(crate) fn since(
+    previous: Option<(Theme, Theme)>,
+    next: Theme,
+    next_resolved: Theme,
+    reason: ChangeReason,
+) -> Option<ThemeChange> {
+    let (previous, previous_resolved) = previous?;
+    let change = ThemeChange {
+        previous,
+        next,
+        previous_resolved,
+        next_resolved,
+        reason,
+    };
+    change.is_effective().then_some(change)
+}
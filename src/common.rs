@@ -1,9 +1,31 @@
+use crate::error::{ThemeError, ThemeErrors};
+use serde::de::DeserializeSeed;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::rc::Rc;
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", any(feature = "yew", feature = "dio")))]
 pub(crate) const SYSTEM_THEME_QUERY: &str = "(prefers-color-scheme: dark)";
+#[cfg(all(target_arch = "wasm32", any(feature = "yew", feature = "dio")))]
+pub(crate) const CONTRAST_MORE_QUERY: &str = "(prefers-contrast: more)";
+#[cfg(all(target_arch = "wasm32", any(feature = "yew", feature = "dio")))]
+pub(crate) const CONTRAST_LESS_QUERY: &str = "(prefers-contrast: less)";
+#[cfg(all(target_arch = "wasm32", any(feature = "yew", feature = "dio")))]
+pub(crate) const FORCED_COLORS_QUERY: &str = "(forced-colors: active)";
+#[cfg(all(target_arch = "wasm32", any(feature = "yew", feature = "dio")))]
+pub(crate) const REDUCED_MOTION_QUERY: &str = "(prefers-reduced-motion: reduce)";
+#[cfg(all(target_arch = "wasm32", any(feature = "yew", feature = "dio")))]
+pub(crate) const REDUCED_TRANSPARENCY_QUERY: &str = "(prefers-reduced-transparency: reduce)";
+#[cfg(all(target_arch = "wasm32", any(feature = "yew", feature = "dio")))]
+pub(crate) const REDUCED_DATA_QUERY: &str = "(prefers-reduced-data: reduce)";
+
+/// Prefix of the CSS custom properties written for each color token, e.g. `--theme-primary`.
+pub const CSS_VARIABLE_PREFIX: &str = "--theme-";
+
+/// Style injected while a theme is applied when providers disable transitions.
+pub const DISABLE_TRANSITIONS_CSS: &str = "*,*::before,*::after{transition:none!important}";
+
+#[cfg(any(feature = "yew", feature = "dio"))]
 pub(crate) const DEFAULT_STORAGE_KEY: &str = "theme";
 
 /// Enum representing browser storage options for persisting the selected theme.
@@ -16,6 +38,223 @@ pub enum StorageType {
     SessionStorage,
 }
 
+/// Parses a `#rgb` or `#rrggbb` hex color (the `#` is optional) into its RGB channels.
+pub(crate) fn parse_hex(color: &str) -> Option<[u8; 3]> {
+    let hex = color.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    match hex.len() {
+        3 => {
+            let mut rgb = [0u8; 3];
+            for (i, c) in hex.chars().enumerate() {
+                let v = c.to_digit(16)? as u8;
+                rgb[i] = v * 17;
+            }
+            Some(rgb)
+        }
+        6 => Some([
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        ]),
+        _ => None,
+    }
+}
+
+/// Formats RGB channels as a lowercase `#rrggbb` hex color.
+pub(crate) fn format_hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Mixes two colors, `amount` being the share of `to` from `0.0` to `1.0`.
+pub(crate) fn mix_rgb(from: [u8; 3], to: [u8; 3], amount: f64) -> [u8; 3] {
+    let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * amount).round() as u8;
+    [
+        channel(from[0], to[0]),
+        channel(from[1], to[1]),
+        channel(from[2], to[2]),
+    ]
+}
+
+/// Returns the WCAG 2 relative luminance of a hex color, from `0.0` (black) to `1.0` (white).
+pub fn relative_luminance(color: &str) -> Option<f64> {
+    let linear = |channel: u8| {
+        let c = channel as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let [r, g, b] = parse_hex(color)?;
+    Some(0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b))
+}
+
+/// Returns the WCAG 2 contrast ratio between two hex colors, from `1.0` to `21.0`.
+///
+/// Body text needs at least `4.5` to meet level AA, large text and UI components `3.0`.
+pub fn contrast_ratio(foreground: &str, background: &str) -> Option<f64> {
+    let a = relative_luminance(foreground)?;
+    let b = relative_luminance(background)?;
+    Some((a.max(b) + 0.05) / (a.min(b) + 0.05))
+}
+
+/// Moves `color` towards `target` until it reaches `min_ratio` against `background`.
+fn raise_contrast(color: &str, target: [u8; 3], background: &str, min_ratio: f64) -> String {
+    let Some(rgb) = parse_hex(color) else {
+        return color.to_string();
+    };
+    (0..=20)
+        .map(|step| format_hex(mix_rgb(rgb, target, step as f64 / 20.0)))
+        .find(|candidate| contrast_ratio(candidate, background).unwrap_or(0.0) >= min_ratio)
+        .unwrap_or_else(|| format_hex(target))
+}
+
+/// Moves `color` up to halfway towards `background` while it keeps `min_ratio` against it.
+fn lower_contrast(color: &str, background: &str, min_ratio: f64) -> String {
+    let (Some(rgb), Some(bg)) = (parse_hex(color), parse_hex(background)) else {
+        return color.to_string();
+    };
+    (1..=10)
+        .map(|step| format_hex(mix_rgb(rgb, bg, step as f64 / 20.0)))
+        .take_while(|candidate| contrast_ratio(candidate, background).unwrap_or(0.0) >= min_ratio)
+        .last()
+        .unwrap_or_else(|| color.to_string())
+}
+
+/// How a provider writes the resolved theme to its root element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThemeAttribute {
+    /// A data attribute, such as `data-theme`.
+    Data(String),
+    /// A class toggled through `classList`, leaving other classes in place.
+    Class,
+    /// Both a data attribute and a class.
+    Both(String),
+}
+
+impl Default for ThemeAttribute {
+    fn default() -> Self {
+        ThemeAttribute::Both("data-theme".to_string())
+    }
+}
+
+impl ThemeAttribute {
+    /// Returns the data attribute written, if any.
+    pub fn data_attribute(&self) -> Option<&str> {
+        match self {
+            ThemeAttribute::Data(name) | ThemeAttribute::Both(name) => Some(name),
+            ThemeAttribute::Class => None,
+        }
+    }
+
+    /// Returns `true` if the theme is toggled as a class.
+    pub fn uses_class(&self) -> bool {
+        matches!(self, ThemeAttribute::Class | ThemeAttribute::Both(_))
+    }
+}
+
+/// How a theme switch is animated through the View Transitions API.
+///
+/// Browsers without `document.startViewTransition`, and users who prefer reduced motion, get the
+/// new theme right away.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ThemeTransition {
+    /// Switch without animating.
+    #[default]
+    None,
+    /// Fade from the old theme to the new one.
+    CrossFade { duration_ms: u32 },
+    /// Reveal the new theme in a circle growing from the origin of the switch, such as the
+    /// click point, or from the center of the viewport.
+    CircularReveal { duration_ms: u32 },
+}
+
+impl ThemeTransition {
+    /// A 300ms cross-fade.
+    pub fn cross_fade() -> Self {
+        ThemeTransition::CrossFade { duration_ms: 300 }
+    }
+
+    /// A 500ms circular reveal.
+    pub fn circular_reveal() -> Self {
+        ThemeTransition::CircularReveal { duration_ms: 500 }
+    }
+}
+
+/// The user's `prefers-contrast` media preference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Contrast {
+    #[default]
+    NoPreference,
+    /// `prefers-contrast: more`.
+    More,
+    /// `prefers-contrast: less`.
+    Less,
+}
+
+impl Contrast {
+    /// Returns the media feature value, as written to the `data-contrast` attribute.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Contrast::NoPreference => "no-preference",
+            Contrast::More => "more",
+            Contrast::Less => "less",
+        }
+    }
+
+    /// Returns the suffix naming the variant of a theme for this preference.
+    pub fn variant_suffix(&self) -> Option<&'static str> {
+        match self {
+            Contrast::NoPreference => None,
+            Contrast::More => Some("-high-contrast"),
+            Contrast::Less => Some("-low-contrast"),
+        }
+    }
+}
+
+impl std::str::FromStr for Contrast {
+    type Err = ThemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "no-preference" => Ok(Contrast::NoPreference),
+            "more" => Ok(Contrast::More),
+            "less" => Ok(Contrast::Less),
+            other => Err(ThemeError::UnknownContrast(other.to_string())),
+        }
+    }
+}
+
+/// The user's `prefers-reduced-*` media preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UserPreferences {
+    /// `prefers-reduced-motion: reduce`.
+    pub reduced_motion: bool,
+    /// `prefers-reduced-transparency: reduce`.
+    pub reduced_transparency: bool,
+    /// `prefers-reduced-data: reduce`.
+    pub reduced_data: bool,
+}
+
+impl UserPreferences {
+    /// Returns the root attributes describing these preferences, e.g.
+    /// `("data-reduced-motion", "reduce")`.
+    pub fn attributes(&self) -> [(&'static str, &'static str); 3] {
+        let value = |reduce: bool| if reduce { "reduce" } else { "no-preference" };
+        [
+            ("data-reduced-motion", value(self.reduced_motion)),
+            (
+                "data-reduced-transparency",
+                value(self.reduced_transparency),
+            ),
+            ("data-reduced-data", value(self.reduced_data)),
+        ]
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ColorTokens {
     pub primary: String,
@@ -28,6 +267,58 @@ pub struct ColorTokens {
 }
 
 impl ColorTokens {
+    /// Returns the CSS system color keywords browsers use in forced colors mode, such as
+    /// Windows High Contrast.
+    ///
+    /// Semantic tokens have no system color equivalent and are left unset.
+    pub fn system_colors() -> ColorTokens {
+        ColorTokens {
+            primary: "LinkText".into(),
+            secondary: "ButtonText".into(),
+            background: "Canvas".into(),
+            text: "CanvasText".into(),
+            error: None,
+            warning: None,
+            success: None,
+        }
+    }
+
+    /// Returns every token with its name, optional tokens being `None` when unset.
+    pub(crate) fn fields(&self) -> [(&'static str, Option<&String>); 7] {
+        [
+            ("primary", Some(&self.primary)),
+            ("secondary", Some(&self.secondary)),
+            ("background", Some(&self.background)),
+            ("text", Some(&self.text)),
+            ("error", self.error.as_ref()),
+            ("warning", self.warning.as_ref()),
+            ("success", self.success.as_ref()),
+        ]
+    }
+
+    /// Returns the tokens as CSS custom properties, e.g. `("--theme-primary", "#268bd2")`.
+    /// Unset optional tokens are left out.
+    pub fn css_variables(&self) -> Vec<(String, String)> {
+        self.fields()
+            .into_iter()
+            .filter_map(|(name, value)| {
+                value.map(|value| (format!("{}{}", CSS_VARIABLE_PREFIX, name), value.clone()))
+            })
+            .collect()
+    }
+
+    /// Replaces `primary` with the `accent` hex color. Anything else, such as an empty string,
+    /// leaves the tokens unchanged.
+    pub fn with_accent(&self, accent: &str) -> ColorTokens {
+        match parse_hex(accent) {
+            Some(_) => ColorTokens {
+                primary: accent.to_string(),
+                ..self.clone()
+            },
+            None => self.clone(),
+        }
+    }
+
     pub fn merge_with(&self, other: &ColorTokens) -> ColorTokens {
         ColorTokens {
             primary: other.primary.clone(),
@@ -40,24 +331,64 @@ impl ColorTokens {
         }
     }
 
-    pub fn validate(&self) -> Result<(), String> {
-        fn is_valid_hex(color: &str) -> bool {
-            let color = color.trim_start_matches('#');
-            color.len() == 6 || color.len() == 3 && u32::from_str_radix(color, 16).is_ok()
+    /// Derives a high contrast variant.
+    ///
+    /// The background becomes pure black or white, whichever is closer, and every other color
+    /// moves towards the opposite pole until it reaches a 7:1 contrast ratio (WCAG AAA).
+    pub fn high_contrast(&self) -> ColorTokens {
+        let dark = relative_luminance(&self.background).unwrap_or(1.0) < 0.18;
+        let (background, pole) = if dark {
+            ("#000000", [0xff, 0xff, 0xff])
+        } else {
+            ("#ffffff", [0x00, 0x00, 0x00])
+        };
+        let raise = |color: &str| raise_contrast(color, pole, background, 7.0);
+
+        ColorTokens {
+            primary: raise(&self.primary),
+            secondary: raise(&self.secondary),
+            background: background.to_string(),
+            text: format_hex(pole),
+            error: self.error.as_deref().map(raise),
+            warning: self.warning.as_deref().map(raise),
+            success: self.success.as_deref().map(raise),
         }
+    }
+
+    /// Derives a low contrast variant.
+    ///
+    /// The background is kept and every other color moves towards it, while text keeps a 4.5:1
+    /// and every other token a 3:1 contrast ratio (WCAG AA).
+    pub fn low_contrast(&self) -> ColorTokens {
+        let lower = |color: &str| lower_contrast(color, &self.background, 3.0);
 
-        for (field_name, value) in [
-            ("primary", &self.primary),
-            ("secondary", &self.secondary),
-            ("background", &self.background),
-            ("text", &self.text),
-        ] {
-            if !is_valid_hex(value) {
-                return Err(format!("Invalid hex color for '{}': {}", field_name, value));
+        ColorTokens {
+            primary: lower(&self.primary),
+            secondary: lower(&self.secondary),
+            background: self.background.clone(),
+            text: lower_contrast(&self.text, &self.background, 4.5),
+            error: self.error.as_deref().map(lower),
+            warning: self.warning.as_deref().map(lower),
+            success: self.success.as_deref().map(lower),
+        }
+    }
+
+    /// Checks every color token, reporting all invalid colors at once.
+    pub fn validate(&self) -> Result<(), ThemeErrors> {
+        let mut errors = ThemeErrors::new();
+
+        for (field_name, value) in self.fields() {
+            if let Some(value) = value {
+                if parse_hex(value).is_none() {
+                    errors.push(ThemeError::InvalidColor {
+                        field: field_name.to_string(),
+                        value: value.clone(),
+                    });
+                }
             }
         }
 
-        Ok(())
+        errors.into_result(())
     }
 }
 
@@ -69,33 +400,113 @@ pub struct CustomTheme {
 }
 
 impl CustomTheme {
-    pub fn validate(&self) -> Result<(), String> {
+    /// Checks the name and every color token, reporting all problems at once.
+    pub fn validate(&self) -> Result<(), ThemeErrors> {
+        let mut errors = ThemeErrors::new();
         if self.name.trim().is_empty() {
-            return Err("Theme name cannot be empty.".to_string());
+            errors.push(ThemeError::EmptyName);
         }
-
-        self.tokens.validate()?;
-        Ok(())
+        if let Err(token_errors) = self.tokens.validate() {
+            errors.extend(token_errors);
+        }
+        errors.into_result(())
     }
 
-    /// Compose this theme with a base theme if `base` is provided.
+    /// Compose this theme with its chain of base themes.
+    ///
+    /// `base` may name an entry of `available_themes` or one of the built-in `light` and `dark`
+    /// themes, and each base may have a base of its own. Tokens are merged from the root of the
+    /// chain down to this theme.
     pub fn compose_with_base(
         &self,
         available_themes: &HashMap<String, Rc<CustomTheme>>,
-    ) -> Result<ColorTokens, String> {
-        if let Some(ref base_name) = self.base {
+    ) -> Result<ColorTokens, ThemeError> {
+        let (mut ancestors, builtin) = self.ancestors(available_themes)?;
+
+        let mut tokens = match builtin {
+            Some(builtin) => builtin.colors(None),
+            None => ancestors.pop().unwrap_or(self).tokens.clone(),
+        };
+        for theme in ancestors.into_iter().rev() {
+            tokens = tokens.merge_with(&theme.tokens);
+        }
+        Ok(tokens)
+    }
+
+    /// Returns the names of this theme and all of its ancestors, nearest first.
+    pub fn inheritance_chain(
+        &self,
+        available_themes: &HashMap<String, Rc<CustomTheme>>,
+    ) -> Result<Vec<String>, ThemeError> {
+        let (ancestors, builtin) = self.ancestors(available_themes)?;
+        let mut chain: Vec<String> = ancestors.iter().map(|theme| theme.name.clone()).collect();
+        chain.extend(builtin.map(|theme| theme.as_str()));
+        Ok(chain)
+    }
+
+    /// Walks the `base` references, returning this theme followed by its custom ancestors and
+    /// the built-in theme the chain ends on, if any.
+    fn ancestors<'a>(
+        &'a self,
+        available_themes: &'a HashMap<String, Rc<CustomTheme>>,
+    ) -> Result<(Vec<&'a CustomTheme>, Option<Theme>), ThemeError> {
+        let mut chain = vec![self.name.as_str()];
+        let mut ancestors = vec![self];
+        let mut next = self.base.as_deref();
+
+        while let Some(base_name) = next {
+            let cyclic = chain.contains(&base_name);
+            chain.push(base_name);
+            if cyclic {
+                return Err(ThemeError::InheritanceCycle {
+                    chain: chain.into_iter().map(String::from).collect(),
+                });
+            }
+
             if let Some(base_theme) = available_themes.get(base_name) {
-                Ok(base_theme.tokens.merge_with(&self.tokens))
+                ancestors.push(base_theme);
+                next = base_theme.base.as_deref();
+            } else if let Some(builtin) = Theme::builtin_base(base_name) {
+                return Ok((ancestors, Some(builtin)));
             } else {
-                Err(format!("Base theme '{}' not found.", base_name))
+                return Err(ThemeError::MissingBase {
+                    base: base_name.to_string(),
+                    chain: chain.into_iter().map(String::from).collect(),
+                });
             }
-        } else {
-            Ok(self.tokens.clone())
         }
+
+        Ok((ancestors, None))
+    }
+}
+
+/// Prefix of the string encoding of [`Theme::Custom`], see [`Theme`]'s `Display` impl.
+pub const CUSTOM_THEME_PREFIX: &str = "custom:";
+
+/// Looks up custom themes by name, e.g. when parsing a stored [`Theme`].
+pub trait ThemeLookup {
+    /// Returns the custom theme registered under `name`.
+    fn lookup_theme(&self, name: &str) -> Option<Rc<CustomTheme>>;
+}
+
+impl ThemeLookup for HashMap<String, Rc<CustomTheme>> {
+    fn lookup_theme(&self, name: &str) -> Option<Rc<CustomTheme>> {
+        self.get(name).cloned()
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+/// A theme selection.
+///
+/// # Encoding
+///
+/// `Display` produces a stable string encoding that [`Theme::parse_with`] reads back:
+/// `light`, `dark`, `system`, or `custom:<name>` for custom themes. [`Theme::as_str`] is the
+/// value written to DOM attributes and is the bare theme name instead.
+///
+/// With serde, themes serialize by value: built-in themes as their encoding and custom themes
+/// as the full [`CustomTheme`]. Use [`by_reference`] to serialize custom themes as
+/// `custom:<name>` instead, and [`ThemeSeed`] to deserialize references against a registry.
+#[derive(Clone, Debug, PartialEq, Default)]
 pub enum Theme {
     Light,
     Dark,
@@ -103,20 +514,55 @@ pub enum Theme {
     System,
     Custom(Rc<CustomTheme>),
 }
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Theme::Custom(custom) => write!(f, "{}{}", CUSTOM_THEME_PREFIX, custom.name),
+            builtin => f.write_str(&builtin.as_str()),
+        }
+    }
+}
+
+/// Parses the built-in themes only, as custom themes need a registry to resolve.
+/// Use [`Theme::parse_with`] for those.
 impl std::str::FromStr for Theme {
-    type Err = ();
+    type Err = ThemeError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "light" | "Light" => Ok(Theme::Light),
             "dark" | "Dark" => Ok(Theme::Dark),
             "system" | "System" => Ok(Theme::System),
-            _ => Err(()),
+            _ => Err(ThemeError::UnknownTheme(s.to_string())),
         }
     }
 }
 
 impl Theme {
+    /// Parses a theme encoding, resolving custom themes through `registry`.
+    ///
+    /// Accepts everything [`FromStr`](std::str::FromStr) does, `custom:<name>`, and bare custom
+    /// theme names as written by earlier versions.
+    pub fn parse_with<L: ThemeLookup + ?Sized>(s: &str, registry: &L) -> Result<Theme, ThemeError> {
+        if let Some(name) = s.strip_prefix(CUSTOM_THEME_PREFIX) {
+            return registry
+                .lookup_theme(name)
+                .map(Theme::Custom)
+                .ok_or_else(|| ThemeError::UnknownTheme(name.to_string()));
+        }
+        s.parse()
+            .or_else(|error| registry.lookup_theme(s).map(Theme::Custom).ok_or(error))
+    }
+
+    /// Returns the built-in theme a custom theme may name as its `base`.
+    fn builtin_base(name: &str) -> Option<Theme> {
+        match name {
+            "light" | "Light" => Some(Theme::Light),
+            "dark" | "Dark" => Some(Theme::Dark),
+            _ => None,
+        }
+    }
+
     pub fn as_str(&self) -> String {
         match self {
             Theme::Light => "light".to_string(),
@@ -126,6 +572,70 @@ impl Theme {
         }
     }
 
+    /// Returns a human-readable name, e.g. `"System"` or the name of a custom theme.
+    pub fn label(&self) -> String {
+        match self {
+            Theme::Light => "Light".to_string(),
+            Theme::Dark => "Dark".to_string(),
+            Theme::System => "System".to_string(),
+            Theme::Custom(custom) => custom.name.clone(),
+        }
+    }
+
+    /// Returns the CSS `color-scheme` of this theme: `light` or `dark`, judged by the background
+    /// of custom themes, or `light dark` for [`Theme::System`].
+    pub fn color_scheme(
+        &self,
+        available_themes: Option<&HashMap<String, Rc<CustomTheme>>>,
+    ) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::System => "light dark",
+            Theme::Custom(_) => {
+                match relative_luminance(&self.colors(available_themes).background) {
+                    Some(luminance) if luminance < 0.18 => "dark",
+                    _ => "light",
+                }
+            }
+        }
+    }
+
+    /// Returns the variant of this resolved theme for a contrast preference.
+    ///
+    /// A custom theme named after this one with the preference's
+    /// [suffix](Contrast::variant_suffix), e.g. `dark-high-contrast`, is used if
+    /// `available_themes` holds one. Otherwise, if `derive` is set, a variant is derived with
+    /// [`ColorTokens::high_contrast`] or [`ColorTokens::low_contrast`].
+    pub fn with_contrast(
+        &self,
+        contrast: Contrast,
+        available_themes: &HashMap<String, Rc<CustomTheme>>,
+        derive: bool,
+    ) -> Theme {
+        let Some(suffix) = contrast.variant_suffix() else {
+            return self.clone();
+        };
+        let name = format!("{}{}", self.as_str(), suffix);
+        if let Some(variant) = available_themes.get(&name) {
+            return Theme::Custom(variant.clone());
+        }
+        if !derive {
+            return self.clone();
+        }
+
+        let tokens = self.colors(Some(available_themes));
+        let tokens = match contrast {
+            Contrast::Less => tokens.low_contrast(),
+            _ => tokens.high_contrast(),
+        };
+        Theme::Custom(Rc::new(CustomTheme {
+            name,
+            tokens,
+            base: None,
+        }))
+    }
+
     pub fn is_dark(&self, system_fallback: Option<bool>) -> bool {
         match self {
             Theme::Dark => true,
@@ -167,16 +677,332 @@ impl Theme {
                 warning: None,
                 success: None,
             },
-            Theme::Custom(custom) => {
-                if let Some(themes) = available_themes {
-                    match custom.compose_with_base(themes) {
-                        Ok(tokens) => tokens,
-                        Err(_) => custom.tokens.clone(),
-                    }
-                } else {
-                    custom.tokens.clone()
-                }
+            Theme::Custom(custom) => custom
+                .compose_with_base(available_themes.unwrap_or(&HashMap::new()))
+                .unwrap_or_else(|_| custom.tokens.clone()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ThemeRepr {
+    Encoded(String),
+    Value(CustomTheme),
+    Tagged {
+        #[serde(rename = "Custom")]
+        custom: CustomTheme,
+    },
+}
+
+/// Returns the themes offered by the prebuilt components: the built-in ones, then custom themes
+/// sorted by name.
+#[cfg(any(feature = "yew", feature = "dio"))]
+pub(crate) fn theme_options(
+    custom_themes: &HashMap<String, Rc<CustomTheme>>,
+    include_system: bool,
+) -> Vec<Theme> {
+    let mut custom: Vec<_> = custom_themes.values().cloned().collect();
+    custom.sort_by(|a, b| a.name.cmp(&b.name));
+    [Theme::Light, Theme::Dark]
+        .into_iter()
+        .chain(include_system.then_some(Theme::System))
+        .chain(custom.into_iter().map(Theme::Custom))
+        .collect()
+}
+
+/// Returns the theme after `current` in `themes`, or before it when `forward` is `false`,
+/// wrapping around at either end.
+#[cfg(any(feature = "yew", feature = "dio"))]
+pub(crate) fn step_theme(themes: &[Theme], current: &Theme, forward: bool) -> Theme {
+    let len = themes.len();
+    if len == 0 {
+        return current.clone();
+    }
+    let next = match themes.iter().position(|theme| theme == current) {
+        Some(index) if forward => (index + 1) % len,
+        Some(index) => (index + len - 1) % len,
+        None => 0,
+    };
+    themes[next].clone()
+}
+
+impl Serialize for Theme {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Theme::Custom(custom) => custom.serialize(serializer),
+            builtin => serializer.collect_str(builtin),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ThemeSeed(&HashMap::new()).deserialize(deserializer)
+    }
+}
+
+/// Serializes custom themes by reference, as `custom:<name>`.
+///
+/// There is no matching `deserialize`: a `deserialize_with` function has no way to receive the
+/// registry the names resolve against. Read references back with [`ThemeSeed`] instead.
+///
+/// ```
+/// use serde::de::DeserializeSeed;
+/// use serde::de::value::{Error, StrDeserializer};
+/// use std::collections::HashMap;
+/// use std::rc::Rc;
+/// use theme::common::ThemeSeed;
+/// use theme::{CustomTheme, Theme};
+///
+/// #[derive(serde::Serialize)]
+/// struct Settings {
+///     #[serde(serialize_with = "theme::common::by_reference::serialize")]
+///     theme: Theme,
+/// }
+///
+/// let brand = Rc::new(CustomTheme {
+///     name: "brand".to_string(),
+///     tokens: Theme::Light.colors(None),
+///     base: None,
+/// });
+/// let registry = HashMap::from([(brand.name.clone(), brand.clone())]);
+///
+/// // `custom:brand` is what `by_reference::serialize` writes for this theme.
+/// let stored = Theme::Custom(brand).to_string();
+/// let theme = ThemeSeed(&registry).deserialize(StrDeserializer::<Error>::new(&stored));
+/// assert_eq!(theme.unwrap(), Theme::Custom(registry["brand"].clone()));
+/// ```
+pub mod by_reference {
+    use super::Theme;
+
+    pub fn serialize<S: serde::Serializer>(
+        theme: &Theme,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(theme)
+    }
+}
+
+/// Deserializes a [`Theme`], resolving `custom:<name>` references through a registry.
+///
+/// Themes serialized by value deserialize without a registry, but references need one.
+pub struct ThemeSeed<'a, L: ?Sized>(pub &'a L);
+
+impl<'de, L: ThemeLookup + ?Sized> DeserializeSeed<'de> for ThemeSeed<'_, L> {
+    type Value = Theme;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Theme, D::Error> {
+        match ThemeRepr::deserialize(deserializer)? {
+            ThemeRepr::Encoded(encoded) => {
+                Theme::parse_with(&encoded, self.0).map_err(serde::de::Error::custom)
+            }
+            ThemeRepr::Value(custom) | ThemeRepr::Tagged { custom } => {
+                Ok(Theme::Custom(Rc::new(custom)))
             }
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A custom theme with fixed tokens besides `primary`, for tests across the crate.
+    pub(crate) fn custom(name: &str, base: Option<&str>, primary: &str) -> Rc<CustomTheme> {
+        Rc::new(CustomTheme {
+            name: name.to_string(),
+            base: base.map(String::from),
+            tokens: ColorTokens {
+                primary: primary.to_string(),
+                secondary: "#222222".to_string(),
+                background: "#ffffff".to_string(),
+                text: "#000000".to_string(),
+                error: None,
+                warning: None,
+                success: None,
+            },
+        })
+    }
+
+    fn registry(themes: &[Rc<CustomTheme>]) -> HashMap<String, Rc<CustomTheme>> {
+        themes
+            .iter()
+            .map(|theme| (theme.name.clone(), theme.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn contrast_rejects_unknown_values() {
+        assert_eq!("more".parse(), Ok(Contrast::More));
+        assert_eq!(
+            "extreme".parse::<Contrast>(),
+            Err(ThemeError::UnknownContrast("extreme".to_string()))
+        );
+    }
+
+    #[test]
+    fn compose_merges_a_multi_level_chain_from_the_root() {
+        let mut root = (*custom("root", None, "#000001")).clone();
+        root.tokens.warning = Some("#aa0000".to_string());
+        let mut middle = (*custom("middle", Some("root"), "#000002")).clone();
+        middle.tokens.error = Some("#bb0000".to_string());
+        let leaf = custom("leaf", Some("middle"), "#000003");
+        let themes = registry(&[Rc::new(root), Rc::new(middle), leaf.clone()]);
+
+        let tokens = leaf.compose_with_base(&themes).unwrap();
+        assert_eq!(tokens.primary, "#000003");
+        assert_eq!(tokens.error.as_deref(), Some("#bb0000"));
+        assert_eq!(tokens.warning.as_deref(), Some("#aa0000"));
+        assert_eq!(tokens.success, None);
+        assert_eq!(
+            leaf.inheritance_chain(&themes).unwrap(),
+            ["leaf", "middle", "root"]
+        );
+    }
+
+    #[test]
+    fn compose_overrides_a_builtin_base() {
+        let mut brand = (*custom("brand", Some("dark"), "#000001")).clone();
+        brand.tokens.background = "#101010".to_string();
+        brand.tokens.error = Some("#aa0000".to_string());
+        let child = custom("midnight", Some("brand"), "#123456");
+        let themes = registry(&[Rc::new(brand.clone()), child.clone()]);
+        let dark = Theme::Dark.colors(None);
+
+        let tokens = child.compose_with_base(&themes).unwrap();
+        assert_eq!(tokens.primary, "#123456");
+        assert_eq!(tokens.error.as_deref(), Some("#aa0000"));
+        assert_ne!(tokens.background, dark.background);
+        assert_eq!(
+            tokens,
+            dark.merge_with(&brand.tokens).merge_with(&child.tokens)
+        );
+        assert_eq!(
+            child.inheritance_chain(&themes).unwrap(),
+            ["midnight", "brand", "dark"]
+        );
+    }
+
+    #[test]
+    fn compose_reports_a_cycle_with_its_chain() {
+        let a = custom("a", Some("b"), "#000001");
+        let b = custom("b", Some("c"), "#000002");
+        let c = custom("c", Some("a"), "#000003");
+        let themes = registry(&[a.clone(), b, c]);
+
+        assert_eq!(
+            a.compose_with_base(&themes),
+            Err(ThemeError::InheritanceCycle {
+                chain: vec![
+                    "a".to_string(),
+                    "b".to_string(),
+                    "c".to_string(),
+                    "a".to_string()
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn compose_reports_a_missing_base() {
+        let orphan = custom("orphan", Some("nowhere"), "#000001");
+        let themes = registry(std::slice::from_ref(&orphan));
+
+        assert!(matches!(
+            orphan.compose_with_base(&themes),
+            Err(ThemeError::MissingBase { base, .. }) if base == "nowhere"
+        ));
+    }
+
+    #[test]
+    fn parse_with_resolves_custom_themes() {
+        let brand = custom("brand", None, "#000001");
+        let dark = custom("dark", None, "#000002");
+        let themes = registry(&[brand.clone(), dark]);
+
+        assert_eq!(
+            Theme::parse_with("custom:brand", &themes),
+            Ok(Theme::Custom(brand.clone()))
+        );
+        assert_eq!(
+            Theme::parse_with("brand", &themes),
+            Ok(Theme::Custom(brand))
+        );
+        // Built-in names win over a custom theme with the same name, unless it is prefixed.
+        assert_eq!(Theme::parse_with("dark", &themes), Ok(Theme::Dark));
+        assert!(matches!(
+            Theme::parse_with("custom:dark", &themes),
+            Ok(Theme::Custom(custom)) if custom.name == "dark"
+        ));
+        assert_eq!(
+            Theme::parse_with("custom:missing", &themes),
+            Err(ThemeError::UnknownTheme("missing".to_string()))
+        );
+        assert_eq!(
+            Theme::parse_with("missing", &themes),
+            Err(ThemeError::UnknownTheme("missing".to_string()))
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serde_round_trips_themes_by_value() {
+        let brand = Theme::Custom(custom("brand", Some("dark"), "#000001"));
+        for theme in [Theme::Light, Theme::Dark, Theme::System, brand] {
+            let json = serde_json::to_string(&theme).unwrap();
+            assert_eq!(serde_json::from_str::<Theme>(&json).unwrap(), theme);
+        }
+        assert_eq!(serde_json::to_string(&Theme::Dark).unwrap(), "\"dark\"");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serde_round_trips_themes_by_reference() {
+        #[derive(Serialize)]
+        struct Settings {
+            #[serde(serialize_with = "by_reference::serialize")]
+            theme: Theme,
+        }
+
+        let brand = custom("brand", None, "#000001");
+        let themes = registry(std::slice::from_ref(&brand));
+        let json = serde_json::to_value(Settings {
+            theme: Theme::Custom(brand.clone()),
+        })
+        .unwrap();
+        assert_eq!(json["theme"], "custom:brand");
+
+        let restored = ThemeSeed(&themes).deserialize(&json["theme"]).unwrap();
+        assert_eq!(restored, Theme::Custom(brand));
+        assert!(
+            ThemeSeed(&HashMap::<String, Rc<CustomTheme>>::new())
+                .deserialize(&json["theme"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn validate_reports_every_problem_at_once() {
+        let mut theme = (*custom(" ", None, "blue")).clone();
+        theme.tokens.background = "#ggg".to_string();
+        theme.tokens.warning = Some("#12345".to_string());
+        theme.tokens.success = Some("#00ff00".to_string());
+
+        let invalid = |field: &str, value: &str| ThemeError::InvalidColor {
+            field: field.to_string(),
+            value: value.to_string(),
+        };
+        let errors = theme.validate().unwrap_err();
+        assert_eq!(
+            errors.errors(),
+            [
+                ThemeError::EmptyName,
+                invalid("primary", "blue"),
+                invalid("background", "#ggg"),
+                invalid("warning", "#12345"),
+            ]
+        );
+        assert_eq!(errors.to_string().split("; ").count(), 4);
+    }
+}
@@ -1,34 +1,140 @@
 #![doc = include_str!("../DIOXUS.md")]
 
+pub mod components;
+
 use dioxus::prelude::*;
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::str::FromStr;
+
+pub use components::{ThemeCycleButton, ThemeSelect, ThemeToggle};
 
 #[cfg(target_arch = "wasm32")]
 use web_sys::{
-    HtmlElement, MediaQueryList, Storage, Window,
-    js_sys::Date,
+    HtmlElement, MediaQueryList, Storage,
     wasm_bindgen::{JsCast, prelude::*},
 };
 
 #[cfg(target_arch = "wasm32")]
 use crate::common::SYSTEM_THEME_QUERY;
+#[cfg(target_arch = "wasm32")]
+use crate::dom::{
+    PREFERENCE_QUERIES, apply_theme, current_contrast, current_preferences, disable_transitions,
+    forced_colors_active, migrate_item, on_media_change, set_axis_attributes, set_css_variables,
+    start_view_transition, storage,
+};
 
-use crate::common::{CustomTheme, DEFAULT_STORAGE_KEY, StorageType, Theme};
+#[cfg(target_arch = "wasm32")]
+use crate::axis::contrast_override;
+use crate::axis::{ThemeAxis, apply_accent, initial_values};
+use crate::change::{ChangeReason, ThemeChange};
+use crate::common::{
+    ColorTokens, Contrast, CustomTheme, DEFAULT_STORAGE_KEY, DISABLE_TRANSITIONS_CSS, StorageType,
+    Theme, ThemeAttribute, ThemeTransition, UserPreferences,
+};
+use crate::error::ThemeError;
+use crate::history::{DEFAULT_HISTORY_LIMIT, HistoryEntry, ThemeHistory};
+use crate::policy::ThemePolicy;
+use crate::resolver::{ResolveInput, ThemeResolver};
+use crate::route::{RouteTheme, ThemedRoute};
 
 #[derive(Clone, PartialEq)]
 pub struct ThemeContext {
     pub theme: Signal<Theme>,
     pub resolved_theme: Signal<Theme>,
     pub system_theme: Signal<Theme>,
+    pub contrast: Signal<Contrast>,
+    pub forced_colors: Signal<bool>,
+    pub preferences: Signal<UserPreferences>,
     pub set_theme: Callback<Theme>,
+    /// Sets the theme, animating the switch from an origin such as the click point.
+    pub set_theme_at: Callback<(Theme, (f64, f64))>,
+    /// Sets the theme, or returns why the `ThemePolicy` rejects it.
+    pub try_set_theme: Callback<Theme, Result<(), ThemeError>>,
     pub forced_theme: Option<Theme>,
     pub custom_themes: Signal<HashMap<String, Rc<CustomTheme>>>,
     pub set_custom_theme: Callback<Rc<CustomTheme>>,
     pub reset_to_system: Callback<()>,
     pub preview_theme: Signal<Option<Theme>>,
     pub apply_preview: Callback<Theme>,
+    /// Ends the preview and returns to the selected theme.
+    pub cancel_preview: Callback<()>,
+    /// Ends the preview by selecting the previewed theme.
+    pub commit_preview: Callback<()>,
+    pub axes: Signal<HashMap<String, String>>,
+    pub set_axis: Callback<(String, String)>,
+    /// Theme asked for by the current route, see [`use_route_theme`].
+    pub route_theme: Signal<RouteTheme>,
+    pub set_route_theme: Callback<RouteTheme>,
+    /// Name of the resolver that produced `resolved_theme`, such as `user` or `system`.
+    pub resolved_by: Signal<&'static str>,
+    pub policy: Signal<ThemePolicy>,
+    pub set_policy: Callback<ThemePolicy>,
+    /// Locks every user to a theme, or lifts the lock with `None`.
+    pub set_lock: Callback<Option<Theme>>,
+    /// Reverts the latest recorded theme change.
+    pub undo: Callback<()>,
+    /// Applies again the latest undone theme change.
+    pub redo: Callback<()>,
+    pub can_undo: Memo<bool>,
+    pub can_redo: Memo<bool>,
+    /// Color tokens of the resolved theme, each available as its own memo.
+    pub tokens: TokenStore,
+    /// Context of the nearest enclosing provider, if this one is nested.
+    pub parent: Option<Rc<ThemeContext>>,
+}
+
+/// The color tokens of a provider, with every token exposed as its own memo.
+///
+/// A component that reads only `primary` subscribes only to `primary`, so it doesn't re-render
+/// when another token changes.
+#[derive(Clone, Copy, PartialEq)]
+pub struct TokenStore {
+    pub all: Memo<Rc<ColorTokens>>,
+    pub primary: Memo<String>,
+    pub secondary: Memo<String>,
+    pub background: Memo<String>,
+    pub text: Memo<String>,
+    pub error: Memo<Option<String>>,
+    pub warning: Memo<Option<String>>,
+    pub success: Memo<Option<String>>,
+}
+
+impl TokenStore {
+    /// Creates a memo for each token of `all`. Like any hook, it must be called unconditionally.
+    fn use_tokens(all: Memo<Rc<ColorTokens>>) -> Self {
+        Self {
+            all,
+            primary: use_memo(move || all.read().primary.clone()),
+            secondary: use_memo(move || all.read().secondary.clone()),
+            background: use_memo(move || all.read().background.clone()),
+            text: use_memo(move || all.read().text.clone()),
+            error: use_memo(move || all.read().error.clone()),
+            warning: use_memo(move || all.read().warning.clone()),
+            success: use_memo(move || all.read().success.clone()),
+        }
+    }
+}
+
+impl ThemeContext {
+    /// Returns the color tokens of the resolved theme with the accent axis applied, or the CSS
+    /// system colors while the browser is in forced colors mode.
+    ///
+    /// In a nested provider the tokens are merged onto the parent's.
+    pub fn colors(&self) -> ColorTokens {
+        ColorTokens::clone(&(self.tokens.all)())
+    }
+
+    /// Returns the value in effect on the axis called `name`.
+    pub fn axis(&self, name: &str) -> Option<String> {
+        self.axes.read().get(name).cloned()
+    }
+
+    /// Returns a setter for the axis called `name`.
+    pub fn axis_setter(&self, name: impl Into<String>) -> Callback<String> {
+        let set_axis = self.set_axis;
+        let name = name.into();
+        Callback::new(move |value| set_axis.call((name.clone(), value)))
+    }
 }
 
 #[derive(Props, PartialEq, Clone)]
@@ -45,11 +151,53 @@ pub struct ThemeProviderProps {
     pub forced_theme: Option<Theme>,
     #[props(default)]
     pub custom_themes: HashMap<String, Rc<CustomTheme>>,
+    #[props(default)]
+    pub derive_contrast_themes: bool,
+    #[props(default)]
+    pub emit_css_variables: bool,
+    #[props(default = true)]
+    pub respect_forced_colors: bool,
+    #[props(default)]
+    pub axes: Vec<ThemeAxis>,
+    #[props(default)]
+    pub scoped: bool,
+    #[props(default)]
+    pub attribute: ThemeAttribute,
+    #[props(default)]
+    pub attribute_values: HashMap<String, String>,
+    #[props(default)]
+    pub disable_transitions: bool,
+    #[props(default = DISABLE_TRANSITIONS_CSS)]
+    pub transition_rule: &'static str,
+    #[props(default)]
+    pub transition: ThemeTransition,
+    #[props(default)]
+    pub preview_timeout_ms: Option<u32>,
+    #[props(default = ThemeResolver::defaults())]
+    pub resolvers: Vec<ThemeResolver>,
+    #[props(default)]
+    pub policy: ThemePolicy,
+    #[props(default)]
+    pub on_change: EventHandler<ThemeChange>,
+    #[props(default = DEFAULT_HISTORY_LIMIT)]
+    pub history_limit: usize,
+    #[props(default)]
+    pub persist_history: bool,
 }
 
 #[component]
 pub fn ThemeProvider(props: ThemeProviderProps) -> Element {
-    let val: Option<String> = {
+    // Looked up once, before this provider shadows the parent with its own context.
+    let parent = use_hook(try_consume_context::<Rc<ThemeContext>>);
+    // The wrapper element of a scoped provider, set once it is mounted.
+    #[cfg(target_arch = "wasm32")]
+    let mut scope_element = use_signal(|| None::<HtmlElement>);
+    let scoped = props.scoped;
+    let is_root = parent.is_none() && !scoped;
+
+    let val: Option<String> = if scoped {
+        None
+    } else {
         #[cfg(target_arch = "wasm32")]
         {
             let window = web_sys::window().unwrap();
@@ -70,17 +218,83 @@ pub fn ThemeProvider(props: ThemeProviderProps) -> Element {
 
     let stored_theme = val
         .as_deref()
-        .and_then(|s| Theme::from_str(s).ok())
-        .unwrap_or(props.default_theme);
+        .and_then(|s| Theme::parse_with(s, &props.custom_themes).ok())
+        .filter(|stored| props.policy.permits(stored));
+
+    // A theme set through the global store before the root provider mounted wins.
+    let global = is_root
+        .then(global_theme)
+        .flatten()
+        .filter(|global| props.policy.permits(global));
 
-    let mut theme = use_signal(|| stored_theme.clone());
+    // Whether the user chose the selected theme, rather than it coming from `default_theme`.
+    let mut user_chosen = use_signal(|| stored_theme.is_some() || global.is_some());
+    let mut theme = use_signal(|| {
+        global
+            .or(stored_theme)
+            .unwrap_or(props.default_theme.clone())
+    });
     #[cfg(target_arch = "wasm32")]
     let mut system_theme = use_signal(|| Theme::Light);
     #[cfg(not(target_arch = "wasm32"))]
     let system_theme = use_signal(|| Theme::Light);
+    #[cfg(target_arch = "wasm32")]
+    let mut contrast = use_signal(Contrast::default);
+    #[cfg(not(target_arch = "wasm32"))]
+    let contrast = use_signal(Contrast::default);
+    #[cfg(target_arch = "wasm32")]
+    let mut forced_colors = use_signal(|| false);
+    #[cfg(not(target_arch = "wasm32"))]
+    let forced_colors = use_signal(|| false);
+    #[cfg(target_arch = "wasm32")]
+    let mut preferences = use_signal(UserPreferences::default);
+    #[cfg(not(target_arch = "wasm32"))]
+    let preferences = use_signal(UserPreferences::default);
     let mut resolved_theme = use_signal(|| Theme::Light);
+    let mut resolved_by = use_signal(|| ThemeResolver::Default.name());
     let mut preview_theme = use_signal(|| None::<Theme>);
-    let mut custom_themes_state = use_signal(|| props.custom_themes.clone());
+    let mut route_theme = use_signal(RouteTheme::default);
+    let mut policy = use_signal(|| props.policy.clone());
+    let mut history = use_signal(|| {
+        #[cfg(target_arch = "wasm32")]
+        let history = storage(props.storage_type)
+            .filter(|_| props.persist_history && !scoped)
+            .and_then(|s| {
+                s.get_item(&ThemeHistory::storage_key(props.storage_name))
+                    .ok()
+                    .flatten()
+            })
+            .map(|encoded| {
+                ThemeHistory::decode(&encoded, props.history_limit, &props.custom_themes)
+            })
+            .unwrap_or_else(|| ThemeHistory::new(props.history_limit));
+        #[cfg(not(target_arch = "wasm32"))]
+        let history = ThemeHistory::new(props.history_limit);
+        history
+    });
+    let can_undo = use_memo(move || history.read().can_undo());
+    let can_redo = use_memo(move || history.read().can_redo());
+    // The selected and resolved themes last reported to `on_change`.
+    let mut last_change = use_signal(|| None::<(Theme, Theme)>);
+    let mut custom_themes_state = use_signal(|| {
+        let mut themes = parent
+            .as_ref()
+            .map(|parent| parent.custom_themes.peek().clone())
+            .unwrap_or_default();
+        themes.extend(props.custom_themes.clone());
+        themes
+    });
+    let mut axes_state = use_signal(|| {
+        #[cfg(target_arch = "wasm32")]
+        let stored = |key: &str| {
+            storage(props.storage_type)
+                .filter(|_| !scoped)
+                .and_then(|s| s.get_item(key).ok().flatten())
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let stored = |_: &str| None;
+        initial_values(&props.axes, stored)
+    });
 
     #[cfg(target_arch = "wasm32")]
     let window = use_signal(|| web_sys::window().expect("window not found"));
@@ -97,13 +311,30 @@ pub fn ThemeProvider(props: ThemeProviderProps) -> Element {
             .expect("failed to cast to HtmlElement")
     });
 
+    // Props that can change after mount, kept in signals so that listeners see the latest.
+    let mut forced_theme = use_signal(|| props.forced_theme.clone());
+    let mut storage_name = use_signal(|| props.storage_name);
+    #[cfg(target_arch = "wasm32")]
+    let derive_contrast_themes = props.derive_contrast_themes;
     #[cfg(target_arch = "wasm32")]
-    let forced_theme = props.forced_theme.clone();
+    let emit_css_variables = props.emit_css_variables;
+    #[cfg(target_arch = "wasm32")]
+    let respect_forced_colors = props.respect_forced_colors;
+    #[cfg(target_arch = "wasm32")]
+    let transitions_rule = props.disable_transitions.then_some(props.transition_rule);
 
     let update_resolved = {
-        Callback::new(move |new_theme: Theme| {
+        #[cfg(target_arch = "wasm32")]
+        let attribute = props.attribute.clone();
+        #[cfg(target_arch = "wasm32")]
+        let attribute_values = props.attribute_values.clone();
+        let parent = parent.clone();
+        let resolvers = props.resolvers.clone();
+        let default_theme = props.default_theme.clone();
+        let on_change = props.on_change;
+        Callback::new(move |(new_theme, reason): (Theme, ChangeReason)| {
             #[cfg(target_arch = "wasm32")]
-            {
+            let sys_theme = {
                 let mq = window()
                     .match_media(SYSTEM_THEME_QUERY)
                     .unwrap()
@@ -115,58 +346,133 @@ pub fn ThemeProvider(props: ThemeProviderProps) -> Element {
                     Theme::Light
                 };
                 system_theme.set(sys_theme.clone());
+                sys_theme
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            let sys_theme = Theme::Light;
+            #[cfg(target_arch = "wasm32")]
+            let hour = Some(web_sys::js_sys::Date::new_0().get_hours());
+            #[cfg(not(target_arch = "wasm32"))]
+            let hour = None;
 
-                let binding = preview_theme();
-                let final_theme = if let Some(forced) = &forced_theme {
-                    forced.clone()
-                } else if let Some(preview) = &binding {
-                    preview.clone()
+            let mut notify = |resolved: Theme| {
+                let previous = last_change.replace(Some((new_theme.clone(), resolved.clone())));
+                if let Some(change) =
+                    crate::change::since(previous, new_theme.clone(), resolved, reason)
+                {
+                    on_change.call(change);
+                }
+            };
+            let parent_theme = parent
+                .as_ref()
+                .map(|parent| parent.resolved_theme.peek().clone());
+            let policy = policy.peek().clone();
+            let input = ResolveInput {
+                forced: forced_theme.peek().clone(),
+                locked: policy.lock.clone(),
+                route: route_theme.peek().clone(),
+                preview: preview_theme
+                    .peek()
+                    .clone()
+                    .filter(|preview| policy.permits(preview)),
+                choice: user_chosen
+                    .peek()
+                    .then_some(new_theme.clone())
+                    .filter(|choice| policy.permits(choice)),
+                default: default_theme.clone(),
+                system: sys_theme,
+                parent: parent_theme.clone(),
+                hour,
+            };
+            let (final_theme, resolver) = input.resolve(&resolvers);
+            resolved_by.set(resolver);
+
+            #[cfg(target_arch = "wasm32")]
+            {
+                let current_contrast =
+                    contrast_override(&axes_state.peek()).unwrap_or_else(current_contrast);
+                contrast.set(current_contrast);
+                let forced_colors_active = forced_colors_active();
+                forced_colors.set(forced_colors_active);
+                let current_preferences = current_preferences();
+                preferences.set(current_preferences);
+
+                // A theme inherited from the parent already has the contrast preference applied.
+                let final_theme = if parent_theme.as_ref() == Some(&final_theme) {
+                    final_theme
                 } else {
-                    match new_theme {
-                        Theme::System => sys_theme.clone(),
-                        other => other,
-                    }
+                    final_theme.with_contrast(
+                        current_contrast,
+                        &custom_themes_state.peek(),
+                        derive_contrast_themes,
+                    )
                 };
 
                 resolved_theme.set(final_theme.clone());
-                let _ = html_element().set_attribute("data-theme", &final_theme.as_str());
-                let _ = html_element().set_attribute("class", &final_theme.as_str());
-                let _ = html_element()
-                    .set_attribute("style", &format!("color-scheme: {};", final_theme.as_str()));
+                notify(final_theme.clone());
+                let Some(target) = (if scoped {
+                    scope_element.peek().clone()
+                } else {
+                    Some(html_element())
+                }) else {
+                    return;
+                };
+                let _transitions = disable_transitions(transitions_rule);
+                apply_theme(
+                    &target,
+                    &final_theme,
+                    &attribute,
+                    &attribute_values,
+                    &custom_themes_state.peek(),
+                );
+                let _ = target.set_attribute("data-contrast", current_contrast.as_str());
+                set_axis_attributes(&target, &axes_state.peek());
+                for (name, value) in current_preferences.attributes() {
+                    let _ = target.set_attribute(name, value);
+                }
+                if emit_css_variables {
+                    let tokens = (!(forced_colors_active && respect_forced_colors)).then(|| {
+                        let tokens = apply_accent(
+                            final_theme.colors(Some(&custom_themes_state.peek())),
+                            &axes_state.peek(),
+                        );
+                        // Peeked, so the mount effect calling this doesn't re-run, registering
+                        // its listeners again, whenever the parent's tokens change.
+                        match &parent {
+                            Some(parent) => parent.tokens.all.peek().merge_with(&tokens),
+                            None => tokens,
+                        }
+                    });
+                    set_css_variables(&target, tokens.as_ref());
+                }
             }
 
             #[cfg(not(target_arch = "wasm32"))]
             {
-                resolved_theme.set(match new_theme {
-                    Theme::System => Theme::Light,
-                    other => other,
-                });
+                resolved_theme.set(final_theme.clone());
+                notify(final_theme);
             }
         })
     };
 
-    #[cfg(target_arch = "wasm32")]
-    let storage_name = props.storage_name;
     #[cfg(target_arch = "wasm32")]
     let storage_type = props.storage_type;
+    #[cfg(target_arch = "wasm32")]
+    let scheduled = props
+        .resolvers
+        .iter()
+        .any(|resolver| matches!(resolver, ThemeResolver::Schedule { .. }));
 
     use_effect(move || {
-        update_resolved.call(theme());
+        update_resolved.call((theme.peek().clone(), ChangeReason::System));
 
         #[cfg(target_arch = "wasm32")]
         {
-            let mq = window()
-                .match_media(SYSTEM_THEME_QUERY)
-                .unwrap()
-                .unwrap()
-                .unchecked_into::<MediaQueryList>();
-
-            let closure: Closure<dyn FnMut(web_sys::Event)> = Closure::wrap(Box::new(move |_| {
-                update_resolved.call(theme());
-            }));
-            mq.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref())
-                .unwrap();
-            closure.forget();
+            for query in PREFERENCE_QUERIES {
+                on_media_change(query, move || {
+                    update_resolved.call((theme(), ChangeReason::System))
+                });
+            }
 
             let on_storage: Closure<dyn FnMut(web_sys::StorageEvent)> =
                 Closure::wrap(Box::new(move |_event| {
@@ -175,76 +481,190 @@ pub fn ThemeProvider(props: ThemeProviderProps) -> Element {
                             .unwrap()
                             .local_storage()
                             .unwrap()
-                            .and_then(|s| s.get_item(storage_name).ok())
+                            .and_then(|s| s.get_item(&storage_name.peek()).ok())
                             .expect("stored theme not found"),
                         StorageType::SessionStorage => web_sys::window()
                             .unwrap()
                             .session_storage()
                             .unwrap()
-                            .and_then(|s| s.get_item(storage_name).ok())
+                            .and_then(|s| s.get_item(&storage_name.peek()).ok())
                             .expect("stored theme not found"),
                     };
 
                     if let Some(s) = val {
-                        if let Ok(t) = Theme::from_str(&s) {
+                        if let Ok(t) = Theme::parse_with(&s, &*custom_themes_state.peek()) {
+                            user_chosen.set(true);
                             theme.set(t.clone());
-                            update_resolved.call(t);
+                            update_resolved.call((t, ChangeReason::Storage));
                         }
                     }
                 }));
-            window()
-                .add_event_listener_with_callback("storage", on_storage.as_ref().unchecked_ref())
-                .unwrap();
-            on_storage.forget();
-
-            let interval_closure: Closure<dyn FnMut()> = Closure::wrap(Box::new(move || {
-                let hour = Date::new_0().get_hours();
-                let next = if (7..19).contains(&hour) {
-                    Theme::Light
-                } else {
-                    Theme::Dark
-                };
-                theme.set(next.clone());
-                update_resolved.call(next);
-            }));
-
-            let interval_id = window()
-                .set_interval_with_callback_and_timeout_and_arguments_0(
-                    interval_closure.as_ref().unchecked_ref(),
-                    60_000,
-                )
-                .unwrap();
-            interval_closure.forget();
-
-            window().clear_interval_with_handle(interval_id);
+            if !scoped {
+                window()
+                    .add_event_listener_with_callback(
+                        "storage",
+                        on_storage.as_ref().unchecked_ref(),
+                    )
+                    .unwrap();
+                on_storage.forget();
+            }
+
+            // A schedule is re-evaluated every minute.
+            if scheduled {
+                let interval_closure: Closure<dyn FnMut()> = Closure::wrap(Box::new(move || {
+                    update_resolved.call((theme.peek().clone(), ChangeReason::Schedule))
+                }));
+                window()
+                    .set_interval_with_callback_and_timeout_and_arguments_0(
+                        interval_closure.as_ref().unchecked_ref(),
+                        60_000,
+                    )
+                    .unwrap();
+                interval_closure.forget();
+            }
         }
     });
 
-    let set_theme = {
+    // A nested provider follows its parent when the parent resolves a new theme or its tokens
+    // change.
+    {
+        let parent = parent.clone();
+        use_effect(move || {
+            if let Some(parent) = &parent {
+                parent.resolved_theme.read();
+                parent.tokens.all.read();
+                update_resolved.call((theme.peek().clone(), ChangeReason::System));
+            }
+        });
+    }
+
+    // Re-resolves when the forced theme prop changes.
+    use_effect(use_reactive((&props.forced_theme,), move |(next,)| {
+        if *forced_theme.peek() != next {
+            forced_theme.set(next);
+            update_resolved.call((theme.peek().clone(), ChangeReason::Forced));
+        }
+    }));
+
+    // Custom themes passed after mount are merged with those added through `set_custom_theme`.
+    use_effect(use_reactive(
+        (&props.custom_themes,),
+        move |(custom_themes,)| {
+            let changed = custom_themes
+                .iter()
+                .any(|(name, custom)| custom_themes_state.peek().get(name) != Some(custom));
+            if changed {
+                custom_themes_state.write().extend(custom_themes);
+                update_resolved.call((theme.peek().clone(), ChangeReason::CustomTheme));
+            }
+        },
+    ));
+
+    // Moves the stored theme over when the storage key changes, and applies the theme stored
+    // under the new key.
+    use_effect(use_reactive((&props.storage_name,), move |(next,)| {
+        let previous = *storage_name.peek();
+        if previous == next {
+            return;
+        }
+        storage_name.set(next);
         #[cfg(target_arch = "wasm32")]
-        let storage_name = props.storage_name;
+        if let Some(storage) = storage(storage_type).filter(|_| !scoped) {
+            migrate_item(&storage, previous, next);
+            let stored = storage
+                .get_item(next)
+                .ok()
+                .flatten()
+                .and_then(|value| Theme::parse_with(&value, &*custom_themes_state.peek()).ok());
+            if let Some(stored) = stored {
+                user_chosen.set(true);
+                theme.set(stored.clone());
+                update_resolved.call((stored, ChangeReason::Storage));
+            }
+        }
+    }));
+
+    let change_theme = {
         #[cfg(target_arch = "wasm32")]
         let storage_type = props.storage_type;
-        Callback::new(move |new_theme: Theme| {
+        #[cfg(target_arch = "wasm32")]
+        let transition = props.transition;
+        Callback::new(move |(new_theme, origin): (Theme, Option<(f64, f64)>)| {
+            policy.peek().check(&new_theme)?;
+            user_chosen.set(true);
+            // Selecting a theme ends any preview.
+            if preview_theme.peek().is_some() {
+                preview_theme.set(None);
+            }
             #[cfg(target_arch = "wasm32")]
-            {
+            if !scoped {
                 let _ = match storage_type {
                     StorageType::LocalStorage => web_sys::window()
                         .unwrap()
                         .local_storage()
                         .unwrap()
-                        .and_then(|ls| ls.set_item(storage_name, &new_theme.as_str()).ok()),
+                        .and_then(|ls| {
+                            ls.set_item(&storage_name.peek(), &new_theme.to_string())
+                                .ok()
+                        }),
                     StorageType::SessionStorage => web_sys::window()
                         .unwrap()
                         .session_storage()
                         .unwrap()
-                        .and_then(|ss| ss.set_item(storage_name, &new_theme.as_str()).ok()),
+                        .and_then(|ss| {
+                            ss.set_item(&storage_name.peek(), &new_theme.to_string())
+                                .ok()
+                        }),
                 };
             }
-            theme.set(new_theme.clone());
-            update_resolved.call(new_theme);
+            let update = move || {
+                theme.set(new_theme.clone());
+                update_resolved.call((new_theme, ChangeReason::User));
+            };
+            #[cfg(target_arch = "wasm32")]
+            start_view_transition(transition, origin, update);
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let _ = origin;
+                update();
+            }
+            Ok(())
         })
     };
+    // Changes the theme like `change_theme`, recording the change in the history.
+    let select_theme = Callback::new(move |(new_theme, origin): (Theme, Option<(f64, f64)>)| {
+        let previous = theme.peek().clone();
+        change_theme.call((new_theme.clone(), origin))?;
+        if previous != new_theme {
+            history.write().record(HistoryEntry::Theme {
+                previous,
+                next: new_theme,
+            });
+        }
+        Ok(())
+    });
+    let try_set_theme = Callback::new(move |new_theme| select_theme.call((new_theme, None)));
+    let set_theme = Callback::new(move |new_theme| report_rejection(try_set_theme.call(new_theme)));
+    let set_theme_at = Callback::new(move |(new_theme, origin)| {
+        report_rejection(select_theme.call((new_theme, Some(origin))))
+    });
+
+    let set_policy = Callback::new(move |next: ThemePolicy| {
+        if *policy.peek() != next {
+            policy.set(next);
+            update_resolved.call((theme.peek().clone(), ChangeReason::Policy));
+        }
+    });
+    let set_lock = Callback::new(move |lock: Option<Theme>| {
+        let next = ThemePolicy {
+            lock,
+            ..policy.peek().clone()
+        };
+        set_policy.call(next);
+    });
+    use_effect(use_reactive((&props.policy,), move |(next,)| {
+        set_policy.call(next)
+    }));
 
     let set_custom_theme = {
         Callback::new(move |new_theme: Rc<CustomTheme>| {
@@ -254,48 +674,409 @@ pub fn ThemeProvider(props: ThemeProviderProps) -> Element {
                 return;
             }
             let mut themes = custom_themes_state();
-            themes.insert(new_theme.name.clone(), new_theme);
+            let previous = themes.insert(new_theme.name.clone(), new_theme.clone());
             custom_themes_state.set(themes);
+            update_resolved.call((theme.peek().clone(), ChangeReason::CustomTheme));
+            if previous.as_ref() != Some(&new_theme) {
+                history.write().record(HistoryEntry::CustomTheme {
+                    previous,
+                    next: new_theme,
+                });
+            }
         })
     };
 
+    // Applies a recorded change again, or reverts it when `undo` is set.
+    let apply_history_entry = Callback::new(
+        move |(entry, undo): (HistoryEntry, bool)| -> Result<(), ThemeError> {
+            match entry {
+                HistoryEntry::Theme { previous, next } => {
+                    change_theme.call((if undo { previous } else { next }, None))
+                }
+                HistoryEntry::CustomTheme { previous, next } => {
+                    {
+                        let mut themes = custom_themes_state.write();
+                        match previous.filter(|_| undo) {
+                            Some(previous) => themes.insert(previous.name.clone(), previous),
+                            None if undo => themes.remove(&next.name),
+                            None => themes.insert(next.name.clone(), next),
+                        };
+                    }
+                    update_resolved.call((theme.peek().clone(), ChangeReason::CustomTheme));
+                    Ok(())
+                }
+            }
+        },
+    );
+    let undo = Callback::new(move |_| {
+        let entry = history.write().undo();
+        if let Some(entry) = entry {
+            if let Err(error) = apply_history_entry.call((entry, true)) {
+                history.write().redo();
+                report_rejection(Err(error));
+            }
+        }
+    });
+    let redo = Callback::new(move |_| {
+        let entry = history.write().redo();
+        if let Some(entry) = entry {
+            if let Err(error) = apply_history_entry.call((entry, false)) {
+                history.write().undo();
+                report_rejection(Err(error));
+            }
+        }
+    });
+
+    // Persists the history next to the theme.
+    #[cfg(target_arch = "wasm32")]
+    {
+        let persist_history = props.persist_history;
+        use_effect(move || {
+            let encoded = history.read().encode();
+            if let Some(storage) = storage(storage_type).filter(|_| persist_history && !scoped) {
+                let _ =
+                    storage.set_item(&ThemeHistory::storage_key(&storage_name.peek()), &encoded);
+            }
+        });
+    }
+
     let reset_to_system = {
         Callback::new(move |_| {
             set_theme.call(Theme::System);
         })
     };
 
+    let cancel_preview = Callback::new(move |_| {
+        if preview_theme.peek().is_some() {
+            preview_theme.set(None);
+            update_resolved.call((theme.peek().clone(), ChangeReason::Preview));
+        }
+    });
+
+    let commit_preview = Callback::new(move |_| {
+        let preview = preview_theme.peek().clone();
+        if let Some(preview) = preview {
+            set_theme.call(preview);
+        }
+    });
+
+    let set_route_theme = Callback::new(move |next: RouteTheme| {
+        if *route_theme.peek() != next {
+            route_theme.set(next);
+            update_resolved.call((theme.peek().clone(), ChangeReason::Route));
+        }
+    });
+
+    // Counts previews, so an auto-revert only cancels the preview that scheduled it.
+    let mut preview_generation = use_signal(|| 0u32);
+
     let apply_preview = {
-        Callback::new(move |theme: Theme| {
-            preview_theme.set(Some(theme.clone()));
+        #[cfg(target_arch = "wasm32")]
+        let preview_timeout_ms = props.preview_timeout_ms;
+        Callback::new(move |preview: Theme| {
+            preview_theme.set(Some(preview));
+            update_resolved.call((theme.peek().clone(), ChangeReason::Preview));
+
+            preview_generation += 1;
             #[cfg(target_arch = "wasm32")]
-            {
-                let _ = html_element().set_attribute("data-theme", &theme.as_str());
-                let _ = html_element().set_attribute("class", &theme.as_str());
-                let _ = html_element()
-                    .set_attribute("style", &format!("color-scheme: {};", theme.as_str()));
+            if let Some(timeout_ms) = preview_timeout_ms {
+                let generation = *preview_generation.peek();
+                crate::dom::set_timeout(timeout_ms, move || {
+                    if *preview_generation.peek() == generation {
+                        cancel_preview.call(());
+                    }
+                });
             }
         })
     };
 
+    let set_axis = {
+        let axes = props.axes.clone();
+        #[cfg(target_arch = "wasm32")]
+        let storage_type = props.storage_type;
+        Callback::new(move |(name, value): (String, String)| {
+            let Some(axis) = axes.iter().find(|axis| axis.name == name) else {
+                return;
+            };
+            if axis.forced.is_some() || !axis.accepts(&value) {
+                return;
+            }
+            #[cfg(target_arch = "wasm32")]
+            if let Some(storage) = storage(storage_type).filter(|_| !scoped) {
+                let _ = storage.set_item(&axis.storage_key(), &value);
+            }
+            axes_state.write().insert(name, value);
+            update_resolved.call((theme.peek().clone(), ChangeReason::User));
+        })
+    };
+
+    let all_tokens = {
+        let parent_tokens = parent.as_ref().map(|parent| parent.tokens.all);
+        use_memo(move || {
+            if forced_colors() {
+                return Rc::new(ColorTokens::system_colors());
+            }
+            let tokens = apply_accent(
+                resolved_theme().colors(Some(&custom_themes_state.read())),
+                &axes_state.read(),
+            );
+            Rc::new(match parent_tokens {
+                Some(parent_tokens) => parent_tokens.read().merge_with(&tokens),
+                None => tokens,
+            })
+        })
+    };
+    let tokens = TokenStore::use_tokens(all_tokens);
+
     let context = Rc::new(ThemeContext {
         theme,
         resolved_theme,
         system_theme,
+        contrast,
+        forced_colors,
+        preferences,
         set_theme,
+        set_theme_at,
+        try_set_theme,
         forced_theme: props.forced_theme,
         custom_themes: custom_themes_state,
         set_custom_theme,
         reset_to_system,
         preview_theme,
         apply_preview,
+        cancel_preview,
+        commit_preview,
+        axes: axes_state,
+        set_axis,
+        route_theme,
+        set_route_theme,
+        resolved_by,
+        policy,
+        set_policy,
+        set_lock,
+        undo,
+        redo,
+        can_undo,
+        can_redo,
+        tokens,
+        parent,
+    });
+
+    provide_context(context.clone());
+
+    // The root provider keeps the global store in sync with its selection, in both directions.
+    use_effect(move || {
+        if !is_root {
+            return;
+        }
+        let current = theme();
+        if GLOBAL_THEME.peek().as_ref() != Some(&current) {
+            *GLOBAL_THEME.write() = Some(current);
+        }
+    });
+    use_effect(move || {
+        if !is_root {
+            return;
+        }
+        let Some(next) = GLOBAL_THEME() else {
+            return;
+        };
+        if *theme.peek() == next {
+            return;
+        }
+        if let Err(error) = try_set_theme.call(next) {
+            report_rejection(Err(error));
+            // Keep the store on the theme the provider still shows.
+            *GLOBAL_THEME.write() = Some(theme.peek().clone());
+        }
+    });
+    use_hook(move || {
+        if is_root {
+            // Deferred, since signals shouldn't be written while the component renders.
+            spawn(async move {
+                *GLOBAL_CONTEXT.write() = Some(context);
+            });
+        }
+    });
+    use_drop(move || {
+        if is_root {
+            *GLOBAL_CONTEXT.write() = None;
+        }
     });
 
-    provide_context(context);
+    if scoped {
+        rsx! {
+            div {
+                "data-theme-scope": "",
+                onmounted: move |event: MountedEvent| {
+                    #[cfg(target_arch = "wasm32")]
+                    {
+                        let element = event
+                            .data()
+                            .downcast::<web_sys::Element>()
+                            .and_then(|element| element.clone().dyn_into::<HtmlElement>().ok());
+                        scope_element.set(element);
+                        update_resolved.call((theme.peek().clone(), ChangeReason::System));
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    let _ = event;
+                },
+                {props.children}
+            }
+        }
+    } else {
+        props.children
+    }
+}
 
-    props.children
+/// Logs a theme selection rejected by the `ThemePolicy`.
+fn report_rejection(result: Result<(), ThemeError>) {
+    #[cfg(target_arch = "wasm32")]
+    if let Err(error) = result {
+        web_sys::console::error_1(&format!("Theme policy error: {}", error).into());
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = result;
 }
 
 pub fn use_theme() -> Rc<ThemeContext> {
     consume_context::<Rc<ThemeContext>>()
 }
+
+/// Applies the theme `route` asks for while the calling component is mounted.
+///
+/// Call it from a component below both the router and the `ThemeProvider` with the current
+/// route, e.g. `use_route_theme(use_route::<Route>())`. The user's choice is used again once
+/// a route no longer overrides it.
+pub fn use_route_theme(route: impl ThemedRoute) {
+    let set_route_theme = use_theme().set_route_theme;
+    let route_theme = route.route_theme();
+    use_effect(use_reactive((&route_theme,), move |(route_theme,)| {
+        set_route_theme.call(route_theme)
+    }));
+    use_drop(move || set_route_theme.call(RouteTheme::Inherit));
+}
+
+/// Returns the context of the nearest `ThemeProvider`, or `None` outside of one.
+pub fn try_use_theme() -> Option<Rc<ThemeContext>> {
+    try_consume_context::<Rc<ThemeContext>>()
+}
+
+/// The theme selected in the global store, for code outside the component tree such as router
+/// guards or logging.
+///
+/// The root `ThemeProvider`, the outermost one that is not scoped, publishes its selection
+/// here and applies any theme written to it, subject to its policy. It holds `None` until a
+/// theme was selected or a root provider mounted, and reading or writing it works without a
+/// provider mounted. Reading it in a component subscribes to it.
+pub static GLOBAL_THEME: GlobalSignal<Option<Theme>> = Signal::global(|| None);
+
+/// Context of the mounted root `ThemeProvider`, or `None` while none is mounted.
+pub static GLOBAL_CONTEXT: GlobalSignal<Option<Rc<ThemeContext>>> = Signal::global(|| None);
+
+/// Returns the theme selected in the global store without subscribing to it.
+pub fn global_theme() -> Option<Theme> {
+    GLOBAL_THEME.peek().clone()
+}
+
+/// Selects `theme` in the global store. A mounted root provider applies it as if `set_theme`
+/// was called, and one mounted later starts from it.
+pub fn set_global_theme(theme: Theme) {
+    if GLOBAL_THEME.peek().as_ref() != Some(&theme) {
+        *GLOBAL_THEME.write() = Some(theme);
+    }
+}
+
+/// Returns the context of the mounted root `ThemeProvider` without subscribing to it.
+pub fn global_context() -> Option<Rc<ThemeContext>> {
+    GLOBAL_CONTEXT.peek().clone()
+}
+
+/// Returns the tokens of [`ThemeContext::colors`] as a memo that recomputes only when the
+/// resolved theme, the custom themes, the axes or the forced colors mode change.
+pub fn use_theme_tokens() -> Memo<Rc<ColorTokens>> {
+    use_theme().tokens.all
+}
+
+/// Returns the per-token memos of the nearest provider.
+pub fn use_token_store() -> TokenStore {
+    use_theme().tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dioxus::dioxus_core::NoOpMutations;
+    use std::cell::RefCell;
+
+    struct Pricing;
+
+    impl ThemedRoute for Pricing {
+        fn route_theme(&self) -> RouteTheme {
+            RouteTheme::Forced(Theme::Light)
+        }
+    }
+
+    thread_local! {
+        static HANDLES: RefCell<Option<(Rc<ThemeContext>, Signal<bool>)>> =
+            const { RefCell::new(None) };
+    }
+
+    fn app() -> Element {
+        rsx! {
+            ThemeProvider { default_theme: Theme::Light, Page {} }
+        }
+    }
+
+    #[component]
+    fn Page() -> Element {
+        let on_pricing = use_signal(|| true);
+        let ctx = use_theme();
+        use_hook(|| HANDLES.with(|handles| *handles.borrow_mut() = Some((ctx, on_pricing))));
+        rsx! {
+            if on_pricing() {
+                PricingPage {}
+            }
+        }
+    }
+
+    #[component]
+    fn PricingPage() -> Element {
+        use_route_theme(Pricing);
+        rsx! {}
+    }
+
+    /// Runs renders, tasks and effects until nothing is left to do.
+    fn settle(dom: &mut VirtualDom) {
+        for _ in 0..10 {
+            dom.process_events();
+            dom.render_immediate(&mut NoOpMutations);
+        }
+    }
+
+    #[test]
+    fn leaving_a_forced_route_restores_the_selection_made_on_it() {
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild_in_place();
+        settle(&mut dom);
+        let (ctx, mut on_pricing) = HANDLES.with(|handles| handles.borrow().clone()).unwrap();
+        assert_eq!(dom.in_runtime(|| *ctx.resolved_by.peek()), "route");
+
+        // The route keeps forcing light while the user picks dark on it.
+        dom.in_runtime(|| ctx.set_theme.call(Theme::Dark));
+        settle(&mut dom);
+        assert_eq!(
+            dom.in_runtime(|| ctx.resolved_theme.peek().clone()),
+            Theme::Light
+        );
+
+        // Unmounting the route component applies the latest selection, not the one it mounted with.
+        dom.in_runtime(|| on_pricing.set(false));
+        settle(&mut dom);
+        dom.in_runtime(|| {
+            assert_eq!(*ctx.route_theme.peek(), RouteTheme::Inherit);
+            assert_eq!(*ctx.theme.peek(), Theme::Dark);
+            assert_eq!(*ctx.resolved_theme.peek(), Theme::Dark);
+        });
+    }
+}
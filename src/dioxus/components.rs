@@ -0,0 +1,178 @@
+//! Prebuilt, accessible theme switchers. Each one must be rendered inside a [`ThemeProvider`].
+//!
+//! [`ThemeProvider`]: super::ThemeProvider
+
+use super::use_theme;
+use crate::common::{Theme, step_theme, theme_options};
+use dioxus::prelude::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Props, PartialEq, Clone)]
+pub struct ThemeToggleProps {
+    #[props(default)]
+    pub class: String,
+    #[props(default)]
+    pub button_class: String,
+    #[props(default)]
+    pub active_class: String,
+    #[props(default = "Theme".to_string())]
+    pub aria_label: String,
+}
+
+/// A light, dark and system radio group, navigable with the arrow keys.
+#[component]
+pub fn ThemeToggle(props: ThemeToggleProps) -> Element {
+    let ctx = use_theme();
+    let (theme, set_theme) = (ctx.theme, ctx.set_theme);
+    let options = [Theme::Light, Theme::Dark, Theme::System];
+    let current = theme();
+    let selected = options.iter().position(|option| *option == current);
+    let mut buttons = use_signal(HashMap::<String, Rc<MountedData>>::new);
+
+    let onkeydown = {
+        let options = options.clone();
+        move |event: KeyboardEvent| {
+            let forward = match event.key() {
+                Key::ArrowRight | Key::ArrowDown => true,
+                Key::ArrowLeft | Key::ArrowUp => false,
+                _ => return,
+            };
+            event.prevent_default();
+            let next = step_theme(&options, &theme.peek(), forward);
+            if let Some(button) = buttons.peek().get(&next.to_string()).cloned() {
+                spawn(async move {
+                    let _ = button.set_focus(true).await;
+                });
+            }
+            set_theme.call(next);
+        }
+    };
+
+    rsx! {
+        div {
+            role: "radiogroup",
+            aria_label: "{props.aria_label}",
+            class: "{props.class}",
+            onkeydown,
+            for (index, option) in options.into_iter().enumerate() {
+                {
+                    let checked = selected == Some(index);
+                    let focusable = selected.unwrap_or(0) == index;
+                    let active_class = if checked { props.active_class.as_str() } else { "" };
+                    let key = option.to_string();
+                    let label = option.label();
+                    rsx! {
+                        button {
+                            key: "{key}",
+                            r#type: "button",
+                            role: "radio",
+                            aria_checked: "{checked}",
+                            tabindex: if focusable { "0" } else { "-1" },
+                            class: "{props.button_class} {active_class}",
+                            onmounted: move |event: MountedEvent| {
+                                buttons.write().insert(key.clone(), event.data());
+                            },
+                            onclick: move |_| set_theme.call(option.clone()),
+                            "{label}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn default_cycle() -> Vec<Theme> {
+    vec![Theme::Light, Theme::Dark, Theme::System]
+}
+
+#[derive(Props, PartialEq, Clone)]
+pub struct ThemeCycleButtonProps {
+    #[props(default)]
+    pub class: String,
+    #[props(default = default_cycle())]
+    pub themes: Vec<Theme>,
+}
+
+/// A button stepping through `themes`, light, dark and system by default.
+///
+/// The current theme is exposed as `data-theme-current` for styling, e.g. to show an icon.
+/// Clicks start the switch from the pointer, for a [`ThemeTransition::CircularReveal`].
+///
+/// [`ThemeTransition::CircularReveal`]: crate::ThemeTransition::CircularReveal
+#[component]
+pub fn ThemeCycleButton(props: ThemeCycleButtonProps) -> Element {
+    let ctx = use_theme();
+    let (set_theme, set_theme_at) = (ctx.set_theme, ctx.set_theme_at);
+    let current = (ctx.theme)();
+    let next = step_theme(&props.themes, &current, true);
+    let aria_label = format!("Theme: {}. Switch to {}.", current.label(), next.label());
+
+    let onclick = move |event: MouseEvent| {
+        // Keyboard activation reports a click count of zero.
+        #[cfg(target_arch = "wasm32")]
+        let from_keyboard = event
+            .data()
+            .downcast::<web_sys::MouseEvent>()
+            .is_none_or(|event| event.detail() == 0);
+        #[cfg(not(target_arch = "wasm32"))]
+        let from_keyboard = true;
+        if from_keyboard {
+            set_theme.call(next.clone());
+        } else {
+            let point = event.client_coordinates();
+            set_theme_at.call((next.clone(), (point.x, point.y)));
+        }
+    };
+
+    rsx! {
+        button {
+            r#type: "button",
+            aria_label: "{aria_label}",
+            "data-theme-current": "{current}",
+            class: "{props.class}",
+            onclick,
+            "{current.label()}"
+        }
+    }
+}
+
+#[derive(Props, PartialEq, Clone)]
+pub struct ThemeSelectProps {
+    #[props(default)]
+    pub class: String,
+    #[props(default = "Theme".to_string())]
+    pub aria_label: String,
+    #[props(default = true)]
+    pub include_system: bool,
+}
+
+/// A `<select>` listing the built-in themes and every custom theme of the provider.
+#[component]
+pub fn ThemeSelect(props: ThemeSelectProps) -> Element {
+    let ctx = use_theme();
+    let (custom_themes, set_theme) = (ctx.custom_themes, ctx.set_theme);
+    let current = (ctx.theme)().to_string();
+    let options = theme_options(&custom_themes(), props.include_system);
+
+    rsx! {
+        select {
+            aria_label: "{props.aria_label}",
+            class: "{props.class}",
+            onchange: move |event: FormEvent| {
+                if let Ok(theme) = Theme::parse_with(&event.value(), &*custom_themes.peek()) {
+                    set_theme.call(theme);
+                }
+            },
+            for option in options {
+                option {
+                    key: "{option}",
+                    value: "{option}",
+                    selected: option.to_string() == current,
+                    "{option.label()}"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//! Browser helpers shared by the framework providers.
+
+use crate::axis::CONTRAST_AXIS;
+use crate::common::{
+    CONTRAST_LESS_QUERY, CONTRAST_MORE_QUERY, CSS_VARIABLE_PREFIX, ColorTokens, Contrast,
+    FORCED_COLORS_QUERY, REDUCED_DATA_QUERY, REDUCED_MOTION_QUERY, REDUCED_TRANSPARENCY_QUERY,
+    SYSTEM_THEME_QUERY, Theme, ThemeAttribute, ThemeTransition, UserPreferences,
+};
+use crate::common::{CustomTheme, StorageType};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use web_sys::js_sys::{Array, Function, Object, Promise, Reflect};
+use web_sys::wasm_bindgen::closure::Closure;
+use web_sys::wasm_bindgen::{JsCast, JsValue};
+use web_sys::{HtmlElement, Storage};
+
+/// Media queries whose changes require resolving the theme again.
+pub(crate) const PREFERENCE_QUERIES: [&str; 7] = [
+    SYSTEM_THEME_QUERY,
+    CONTRAST_MORE_QUERY,
+    CONTRAST_LESS_QUERY,
+    FORCED_COLORS_QUERY,
+    REDUCED_MOTION_QUERY,
+    REDUCED_TRANSPARENCY_QUERY,
+    REDUCED_DATA_QUERY,
+];
+
+/// Returns whether `query` currently matches.
+pub(crate) fn media_matches(query: &str) -> bool {
+    web_sys::window()
+        .and_then(|window| window.match_media(query).ok().flatten())
+        .is_some_and(|media_query| media_query.matches())
+}
+
+/// Calls `handler` whenever `query` starts or stops matching, for the lifetime of the page.
+pub(crate) fn on_media_change(query: &str, mut handler: impl FnMut() + 'static) {
+    let Some(media_query) =
+        web_sys::window().and_then(|window| window.match_media(query).ok().flatten())
+    else {
+        return;
+    };
+    let closure = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| handler());
+    let _ =
+        media_query.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref());
+    closure.forget();
+}
+
+/// Returns the current `prefers-contrast` preference.
+pub(crate) fn current_contrast() -> Contrast {
+    if media_matches(CONTRAST_MORE_QUERY) {
+        Contrast::More
+    } else if media_matches(CONTRAST_LESS_QUERY) {
+        Contrast::Less
+    } else {
+        Contrast::NoPreference
+    }
+}
+
+/// Returns the current `prefers-reduced-*` preferences.
+pub(crate) fn current_preferences() -> UserPreferences {
+    UserPreferences {
+        reduced_motion: media_matches(REDUCED_MOTION_QUERY),
+        reduced_transparency: media_matches(REDUCED_TRANSPARENCY_QUERY),
+        reduced_data: media_matches(REDUCED_DATA_QUERY),
+    }
+}
+
+/// Returns whether the browser enforces a limited color palette, such as Windows High Contrast.
+pub(crate) fn forced_colors_active() -> bool {
+    media_matches(FORCED_COLORS_QUERY)
+}
+
+/// Writes `tokens` as `--theme-*` custom properties on `element`, or removes them all for `None`.
+pub(crate) fn set_css_variables(element: &HtmlElement, tokens: Option<&ColorTokens>) {
+    let style = element.style();
+    for (name, _) in ColorTokens::system_colors().fields() {
+        let _ = style.remove_property(&format!("{}{}", CSS_VARIABLE_PREFIX, name));
+    }
+    for (name, value) in tokens.map(ColorTokens::css_variables).unwrap_or_default() {
+        let _ = style.set_property(&name, &value);
+    }
+}
+
+/// Returns the browser storage selected by `storage_type`, if available.
+pub(crate) fn storage(storage_type: StorageType) -> Option<Storage> {
+    let window = web_sys::window()?;
+    match storage_type {
+        StorageType::LocalStorage => window.local_storage(),
+        StorageType::SessionStorage => window.session_storage(),
+    }
+    .ok()
+    .flatten()
+}
+
+/// Moves the value stored under `from` to `to`, keeping any value `to` already holds.
+pub(crate) fn migrate_item(storage: &Storage, from: &str, to: &str) {
+    if let Ok(Some(value)) = storage.get_item(from) {
+        if storage.get_item(to).ok().flatten().is_none() {
+            let _ = storage.set_item(to, &value);
+        }
+        let _ = storage.remove_item(from);
+    }
+}
+
+/// Writes every axis value to its `data-<name>` attribute, removing it for empty values.
+///
+/// The contrast axis is skipped, as `data-contrast` carries the resolved contrast instead.
+pub(crate) fn set_axis_attributes(element: &HtmlElement, values: &HashMap<String, String>) {
+    for (name, value) in values {
+        if name == CONTRAST_AXIS {
+            continue;
+        }
+        let attribute = format!("data-{}", name);
+        if value.is_empty() {
+            let _ = element.remove_attribute(&attribute);
+        } else {
+            let _ = element.set_attribute(&attribute, value);
+        }
+    }
+}
+
+/// Writes `theme` to `element` as configured by `attribute`, mapping theme names through
+/// `values`, and sets its `color-scheme` style property.
+///
+/// Classes are toggled through `classList` and only `color-scheme` is touched in the inline
+/// style, so anything else set on the element survives.
+pub(crate) fn apply_theme(
+    element: &HtmlElement,
+    theme: &Theme,
+    attribute: &ThemeAttribute,
+    values: &HashMap<String, String>,
+    custom_themes: &HashMap<String, Rc<CustomTheme>>,
+) {
+    let value_of = |name: &str| {
+        values
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    };
+    let value = value_of(&theme.as_str());
+
+    if let Some(name) = attribute.data_attribute() {
+        let _ = element.set_attribute(name, &value);
+    }
+    if attribute.uses_class() {
+        let class_list = element.class_list();
+        let suffixes = [Contrast::More, Contrast::Less].map(|c| c.variant_suffix().unwrap_or(""));
+        for name in ["light", "dark"]
+            .into_iter()
+            .chain(custom_themes.keys().map(String::as_str))
+        {
+            for suffix in [""].into_iter().chain(suffixes) {
+                let known = value_of(&format!("{}{}", name, suffix));
+                if known != value {
+                    let _ = class_list.remove_1(&known);
+                }
+            }
+        }
+        if !value.is_empty() {
+            let _ = class_list.add_1(&value);
+        }
+    }
+    let _ = element
+        .style()
+        .set_property("color-scheme", theme.color_scheme(Some(custom_themes)));
+}
+
+/// Keeps a style disabling CSS transitions in the document until dropped.
+pub(crate) struct TransitionGuard(Option<web_sys::Element>);
+
+/// Appends a `<style>` holding `css` to the document head.
+fn inject_style(css: &str) -> Option<web_sys::Element> {
+    let document = web_sys::window()?.document()?;
+    let style = document.create_element("style").ok()?;
+    style.set_text_content(Some(css));
+    document.head()?.append_child(&style).ok()?;
+    Some(style)
+}
+
+/// Injects `rule`, if any, to disable transitions while the returned guard lives.
+pub(crate) fn disable_transitions(rule: Option<&str>) -> TransitionGuard {
+    TransitionGuard(rule.and_then(inject_style))
+}
+
+impl Drop for TransitionGuard {
+    fn drop(&mut self) {
+        let (Some(style), Some(window)) = (self.0.take(), web_sys::window()) else {
+            return;
+        };
+        // Reading a computed style forces a reflow, so the new colors land before transitions
+        // are restored.
+        if let Some(body) = window.document().and_then(|document| document.body()) {
+            if let Ok(Some(computed)) = window.get_computed_style(&body) {
+                let _ = computed.get_property_value("opacity");
+            }
+        }
+        let remove = Closure::once_into_js(move || style.remove());
+        let _ =
+            window.set_timeout_with_callback_and_timeout_and_arguments_0(remove.unchecked_ref(), 1);
+    }
+}
+
+/// Runs `update` inside `document.startViewTransition`, animated as `transition`, or right away
+/// when there is nothing to animate, the API is missing or the user prefers reduced motion.
+pub(crate) fn start_view_transition(
+    transition: ThemeTransition,
+    origin: Option<(f64, f64)>,
+    update: impl FnOnce() + 'static,
+) {
+    let document = web_sys::window().and_then(|window| window.document());
+    let start = document
+        .as_ref()
+        .and_then(|document| Reflect::get(document, &"startViewTransition".into()).ok())
+        .and_then(|start| start.dyn_into::<Function>().ok());
+    let (Some(document), Some(start), Some(duration_ms)) =
+        (document, start, transition_duration(transition))
+    else {
+        update();
+        return;
+    };
+    if media_matches(REDUCED_MOTION_QUERY) {
+        update();
+        return;
+    }
+
+    let style = inject_style(&match transition {
+        ThemeTransition::CircularReveal { .. } => {
+            "::view-transition-old(root),::view-transition-new(root){animation:none;mix-blend-mode:normal}"
+                .to_string()
+        }
+        _ => format!("::view-transition-group(root){{animation-duration:{}ms}}", duration_ms),
+    });
+    // Shared with the transition's callback, so the theme is still applied if the call throws,
+    // e.g. while another transition is running or the document is hidden.
+    let update = Rc::new(Cell::new(Some(update)));
+    let callback = {
+        let update = update.clone();
+        Closure::once_into_js(move || {
+            if let Some(update) = update.take() {
+                update();
+            }
+        })
+    };
+    let Ok(view_transition) = start.call1(&document, &callback) else {
+        if let Some(style) = style {
+            style.remove();
+        }
+        if let Some(update) = update.take() {
+            update();
+        }
+        return;
+    };
+
+    if let ThemeTransition::CircularReveal { .. } = transition {
+        on_settled(&view_transition, "ready", move || {
+            circular_reveal(origin, duration_ms);
+        });
+    }
+    on_settled(&view_transition, "finished", move || {
+        if let Some(style) = style {
+            style.remove();
+        }
+    });
+}
+
+/// Returns the duration of an animated transition.
+fn transition_duration(transition: ThemeTransition) -> Option<u32> {
+    match transition {
+        ThemeTransition::None => None,
+        ThemeTransition::CrossFade { duration_ms }
+        | ThemeTransition::CircularReveal { duration_ms } => Some(duration_ms),
+    }
+}
+
+/// Calls `handler` once the promise stored in `property` of `target` settles.
+fn on_settled(target: &JsValue, property: &str, handler: impl FnOnce() + 'static) {
+    let Some(promise) = Reflect::get(target, &property.into())
+        .ok()
+        .and_then(|promise| promise.dyn_into::<Promise>().ok())
+    else {
+        return;
+    };
+    let handler = std::rc::Rc::new(std::cell::Cell::new(Some(handler)));
+    let settle = move |_: JsValue| {
+        if let Some(handler) = handler.take() {
+            handler();
+        }
+    };
+    let on_fulfilled = Closure::<dyn FnMut(JsValue)>::new(settle.clone());
+    let on_rejected = Closure::<dyn FnMut(JsValue)>::new(settle);
+    let _ = promise.then2(&on_fulfilled, &on_rejected);
+    on_fulfilled.forget();
+    on_rejected.forget();
+}
+
+/// Grows the new view of the root from a circle at `origin`, or at the viewport center.
+fn circular_reveal(origin: Option<(f64, f64)>, duration_ms: u32) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let Some(root) = window
+        .document()
+        .and_then(|document| document.document_element())
+    else {
+        return;
+    };
+    let size = |value: Result<JsValue, JsValue>| value.ok().and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let (width, height) = (size(window.inner_width()), size(window.inner_height()));
+    let (x, y) = origin.unwrap_or((width / 2.0, height / 2.0));
+    let radius = x.max(width - x).hypot(y.max(height - y));
+
+    let keyframe = |radius: f64| {
+        let keyframe = Object::new();
+        let clip_path = format!("circle({}px at {}px {}px)", radius, x, y);
+        let _ = Reflect::set(&keyframe, &"clipPath".into(), &clip_path.into());
+        keyframe
+    };
+    let options = Object::new();
+    let _ = Reflect::set(&options, &"duration".into(), &duration_ms.into());
+    let _ = Reflect::set(&options, &"easing".into(), &"ease-in-out".into());
+    let _ = Reflect::set(
+        &options,
+        &"pseudoElement".into(),
+        &"::view-transition-new(root)".into(),
+    );
+
+    if let Some(animate) = Reflect::get(&root, &"animate".into())
+        .ok()
+        .and_then(|animate| animate.dyn_into::<Function>().ok())
+    {
+        let _ = animate.call2(
+            &root,
+            &Array::of2(&keyframe(0.0), &keyframe(radius)),
+            &options,
+        );
+    }
+}
+
+/// Calls `handler` once after `timeout_ms` milliseconds.
+pub(crate) fn set_timeout(timeout_ms: u32, handler: impl FnOnce() + 'static) {
+    if let Some(window) = web_sys::window() {
+        let handler = Closure::once_into_js(handler);
+        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            handler.unchecked_ref(),
+            timeout_ms as i32,
+        );
+    }
+}
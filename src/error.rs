@@ -0,0 +1,191 @@
+//! Errors reported while validating, composing, parsing and loading themes.
+
+use std::fmt;
+
+/// A single problem with a theme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThemeError {
+    /// A color token is not a valid `#rgb` or `#rrggbb` hex color.
+    InvalidColor { field: String, value: String },
+    /// A custom theme has an empty or blank name.
+    EmptyName,
+    /// A `base` reference names a theme that does not exist.
+    MissingBase { base: String, chain: Vec<String> },
+    /// A chain of `base` references loops back onto itself.
+    InheritanceCycle { chain: Vec<String> },
+    /// A theme name or encoding could not be resolved.
+    UnknownTheme(String),
+    /// A contrast preference is not `no-preference`, `more` or `less`.
+    UnknownContrast(String),
+    /// A theme name is already taken in a registry.
+    DuplicateTheme(String),
+    /// A theme document could not be parsed.
+    Parse {
+        format: &'static str,
+        message: String,
+    },
+    /// A theme file could not be read.
+    Io { path: String, message: String },
+    /// A theme file has an extension no enabled format feature understands.
+    UnsupportedFormat(String),
+    /// A theme is excluded by the provider's `ThemePolicy`.
+    NotAllowed(String),
+    /// The provider's `ThemePolicy` locks every user to the given theme.
+    Locked(String),
+    /// An error attributed to a named theme.
+    InTheme {
+        name: String,
+        error: Box<ThemeError>,
+    },
+}
+
+impl ThemeError {
+    /// Attributes this error to the theme called `name`.
+    pub fn in_theme(self, name: impl Into<String>) -> ThemeError {
+        ThemeError::InTheme {
+            name: name.into(),
+            error: Box::new(self),
+        }
+    }
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::InvalidColor { field, value } => {
+                write!(f, "Invalid hex color for '{}': {}", field, value)
+            }
+            ThemeError::EmptyName => write!(f, "Theme name cannot be empty."),
+            ThemeError::MissingBase { base, chain } => write!(
+                f,
+                "Base theme '{}' not found in chain: {}.",
+                base,
+                chain.join(" -> ")
+            ),
+            ThemeError::InheritanceCycle { chain } => {
+                write!(f, "Inheritance cycle: {}.", chain.join(" -> "))
+            }
+            ThemeError::UnknownTheme(name) => write!(f, "Unknown theme '{}'.", name),
+            ThemeError::UnknownContrast(value) => write!(
+                f,
+                "Unknown contrast preference '{}'. Expected no-preference, more or less.",
+                value
+            ),
+            ThemeError::DuplicateTheme(name) => {
+                write!(f, "Theme '{}' is already registered.", name)
+            }
+            ThemeError::Parse { format, message } => {
+                write!(f, "Invalid {} theme data: {}", format, message)
+            }
+            ThemeError::Io { path, message } => write!(f, "Failed to read '{}': {}", path, message),
+            ThemeError::UnsupportedFormat(path) => write!(
+                f,
+                "Unsupported theme file '{}'. Is the matching format feature enabled?",
+                path
+            ),
+            ThemeError::NotAllowed(theme) => {
+                write!(f, "Theme '{}' is not allowed by the theme policy.", theme)
+            }
+            ThemeError::Locked(theme) => write!(f, "Themes are locked to '{}'.", theme),
+            ThemeError::InTheme { name, error } => write!(f, "{}: {}", name, error),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThemeError::InTheme { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// A report of every problem found, rather than just the first one.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ThemeErrors(Vec<ThemeError>);
+
+impl ThemeErrors {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a problem to the report.
+    pub fn push(&mut self, error: ThemeError) {
+        self.0.push(error);
+    }
+
+    /// Returns the reported problems in the order they were found.
+    pub fn errors(&self) -> &[ThemeError] {
+        &self.0
+    }
+
+    /// Returns the number of reported problems.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if nothing was reported.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the reported problems.
+    pub fn iter(&self) -> std::slice::Iter<'_, ThemeError> {
+        self.0.iter()
+    }
+
+    /// Returns `Ok(value)` if nothing was reported, or the report itself otherwise.
+    pub fn into_result<T>(self, value: T) -> Result<T, ThemeErrors> {
+        if self.is_empty() {
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ThemeErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ThemeErrors {}
+
+impl From<ThemeError> for ThemeErrors {
+    fn from(error: ThemeError) -> Self {
+        ThemeErrors(vec![error])
+    }
+}
+
+impl Extend<ThemeError> for ThemeErrors {
+    fn extend<I: IntoIterator<Item = ThemeError>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for ThemeErrors {
+    type Item = ThemeError;
+    type IntoIter = std::vec::IntoIter<ThemeError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ThemeErrors {
+    type Item = &'a ThemeError;
+    type IntoIter = std::slice::Iter<'a, ThemeError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
@@ -0,0 +1,305 @@
+//! A bounded undo and redo history of theme changes.
+//!
+//! Providers record each `set_theme`, `set_custom_theme` and committed preview in a
+//! [`ThemeHistory`], and expose `undo` and `redo` in their context.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use theme::{HistoryEntry, Theme, ThemeHistory};
+//!
+//! let mut history = ThemeHistory::new(2);
+//! history.record(HistoryEntry::Theme { previous: Theme::System, next: Theme::Light });
+//! history.record(HistoryEntry::Theme { previous: Theme::Light, next: Theme::Dark });
+//! history.record(HistoryEntry::Theme { previous: Theme::Dark, next: Theme::System });
+//! assert_eq!(history.len(), 2);
+//!
+//! assert_eq!(
+//!     history.undo(),
+//!     Some(HistoryEntry::Theme { previous: Theme::Dark, next: Theme::System })
+//! );
+//! assert!(history.can_redo());
+//!
+//! let restored = ThemeHistory::decode(&history.encode(), 2, &HashMap::new());
+//! assert_eq!(restored, history);
+//! ```
+
+use crate::common::{CustomTheme, Theme};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Number of changes a provider remembers by default.
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// One change that can be undone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryEntry {
+    /// The selected theme changed from `previous` to `next`.
+    Theme { previous: Theme, next: Theme },
+    /// A custom theme was added, or replaced `previous` when one had the same name.
+    CustomTheme {
+        previous: Option<Rc<CustomTheme>>,
+        next: Rc<CustomTheme>,
+    },
+}
+
+/// Recorded changes with a cursor separating those to undo from those to redo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeHistory {
+    entries: Vec<HistoryEntry>,
+    cursor: usize,
+    limit: usize,
+}
+
+impl Default for ThemeHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl ThemeHistory {
+    /// Creates a history keeping the latest `limit` changes. A limit of 0 records nothing.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            cursor: 0,
+            limit,
+        }
+    }
+
+    /// Records a change, discarding the changes that could be redone and the oldest change
+    /// once the limit is reached.
+    pub fn record(&mut self, entry: HistoryEntry) {
+        if self.limit == 0 {
+            return;
+        }
+        self.entries.truncate(self.cursor);
+        self.entries.push(entry);
+        if self.entries.len() > self.limit {
+            self.entries.remove(0);
+        }
+        self.cursor = self.entries.len();
+    }
+
+    /// Steps back, returning the change to revert.
+    pub fn undo(&mut self) -> Option<HistoryEntry> {
+        self.cursor = self.cursor.checked_sub(1)?;
+        Some(self.entries[self.cursor].clone())
+    }
+
+    /// Steps forward, returning the change to apply again.
+    pub fn redo(&mut self) -> Option<HistoryEntry> {
+        let entry = self.entries.get(self.cursor)?.clone();
+        self.cursor += 1;
+        Some(entry)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.cursor > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.cursor < self.entries.len()
+    }
+
+    /// Returns the number of recorded changes.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the key a provider persists its history under, next to the theme.
+    pub fn storage_key(storage_name: &str) -> String {
+        format!("{}-history", storage_name)
+    }
+
+    /// Encodes the theme changes for storage, one `previous<TAB>next` line each after a line
+    /// holding the cursor.
+    ///
+    /// Custom theme changes are left out, as custom themes are stored elsewhere.
+    pub fn encode(&self) -> String {
+        let mut cursor = 0;
+        let mut lines = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            if let HistoryEntry::Theme { previous, next } = entry {
+                if index < self.cursor {
+                    cursor += 1;
+                }
+                lines.push(format!("{}\t{}", previous, next));
+            }
+        }
+        lines.insert(0, cursor.to_string());
+        lines.join("\n")
+    }
+
+    /// Decodes a history written by [`ThemeHistory::encode`], resolving custom themes against
+    /// `custom_themes` and skipping changes that no longer parse.
+    pub fn decode(
+        encoded: &str,
+        limit: usize,
+        custom_themes: &HashMap<String, Rc<CustomTheme>>,
+    ) -> Self {
+        let mut lines = encoded.lines();
+        let cursor: usize = lines
+            .next()
+            .and_then(|line| line.parse().ok())
+            .unwrap_or_default();
+        let mut history = Self::new(limit);
+        let mut parsed = 0;
+        let mut parsed_before_cursor: usize = 0;
+        for (index, line) in lines.enumerate() {
+            let entry = line.split_once('\t').and_then(|(previous, next)| {
+                Some(HistoryEntry::Theme {
+                    previous: Theme::parse_with(previous, custom_themes).ok()?,
+                    next: Theme::parse_with(next, custom_themes).ok()?,
+                })
+            });
+            if let Some(entry) = entry {
+                history.record(entry);
+                parsed += 1;
+                if index < cursor {
+                    parsed_before_cursor += 1;
+                }
+            }
+        }
+        // Changes beyond the limit were dropped from the front.
+        let dropped = parsed - history.len();
+        history.cursor = parsed_before_cursor.saturating_sub(dropped);
+        history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::custom;
+
+    fn change(previous: Theme, next: Theme) -> HistoryEntry {
+        HistoryEntry::Theme { previous, next }
+    }
+
+    #[test]
+    fn record_drops_the_oldest_change_past_the_limit() {
+        let mut history = ThemeHistory::new(2);
+        history.record(change(Theme::System, Theme::Light));
+        history.record(change(Theme::Light, Theme::Dark));
+        history.record(change(Theme::Dark, Theme::System));
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.undo(), Some(change(Theme::Dark, Theme::System)));
+        assert_eq!(history.undo(), Some(change(Theme::Light, Theme::Dark)));
+        assert_eq!(history.undo(), None);
+
+        let mut disabled = ThemeHistory::new(0);
+        disabled.record(change(Theme::System, Theme::Light));
+        assert!(disabled.is_empty());
+        assert!(!disabled.can_undo());
+    }
+
+    #[test]
+    fn undo_and_redo_move_the_cursor() {
+        let mut history = ThemeHistory::default();
+        history.record(change(Theme::System, Theme::Light));
+        history.record(change(Theme::Light, Theme::Dark));
+
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+        assert_eq!(history.redo(), None);
+
+        assert_eq!(history.undo(), Some(change(Theme::Light, Theme::Dark)));
+        assert!(history.can_redo());
+        assert_eq!(history.redo(), Some(change(Theme::Light, Theme::Dark)));
+        assert!(!history.can_redo());
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn record_after_undo_drops_the_redo_tail() {
+        let mut history = ThemeHistory::default();
+        history.record(change(Theme::System, Theme::Light));
+        history.record(change(Theme::Light, Theme::Dark));
+        history.undo();
+
+        history.record(change(Theme::Light, Theme::System));
+        assert_eq!(history.len(), 2);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), Some(change(Theme::Light, Theme::System)));
+        assert_eq!(history.undo(), Some(change(Theme::System, Theme::Light)));
+    }
+
+    #[test]
+    fn undo_returns_custom_theme_changes() {
+        let original = custom("brand", None, "#000001");
+        let edited = custom("brand", None, "#000002");
+        let mut history = ThemeHistory::default();
+        history.record(HistoryEntry::CustomTheme {
+            previous: None,
+            next: original.clone(),
+        });
+        history.record(HistoryEntry::CustomTheme {
+            previous: Some(original.clone()),
+            next: edited.clone(),
+        });
+
+        assert_eq!(
+            history.undo(),
+            Some(HistoryEntry::CustomTheme {
+                previous: Some(original.clone()),
+                next: edited,
+            })
+        );
+        assert_eq!(
+            history.undo(),
+            Some(HistoryEntry::CustomTheme {
+                previous: None,
+                next: original,
+            })
+        );
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn encode_round_trips_theme_changes_and_the_cursor() {
+        let brand = custom("brand", None, "#000001");
+        let custom_themes = HashMap::from([(brand.name.clone(), brand.clone())]);
+        let mut history = ThemeHistory::new(5);
+        history.record(change(Theme::System, Theme::Light));
+        history.record(change(Theme::Light, Theme::Custom(brand.clone())));
+        history.record(change(Theme::Custom(brand.clone()), Theme::Dark));
+        history.undo();
+
+        let restored = ThemeHistory::decode(&history.encode(), 5, &custom_themes);
+        assert_eq!(restored, history);
+        assert!(restored.can_redo());
+    }
+
+    #[test]
+    fn encode_leaves_out_custom_theme_changes() {
+        let brand = custom("brand", None, "#000001");
+        let mut history = ThemeHistory::default();
+        history.record(change(Theme::System, Theme::Light));
+        history.record(HistoryEntry::CustomTheme {
+            previous: None,
+            next: brand,
+        });
+        history.record(change(Theme::Light, Theme::Dark));
+        history.undo();
+
+        let mut restored = ThemeHistory::decode(&history.encode(), 50, &HashMap::new());
+        assert_eq!(restored.len(), 2);
+        assert!(restored.can_redo());
+        assert_eq!(restored.undo(), Some(change(Theme::System, Theme::Light)));
+    }
+
+    #[test]
+    fn decode_skips_unknown_themes_and_respects_the_limit() {
+        let encoded = "3\nsystem\tlight\nlight\tmissing-theme\nlight\tdark\ndark\tsystem";
+        let mut history = ThemeHistory::decode(encoded, 2, &HashMap::new());
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.undo(), Some(change(Theme::Light, Theme::Dark)));
+        assert_eq!(history.undo(), None);
+    }
+}
@@ -5,7 +5,20 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
 
+pub mod axis;
+pub mod base16;
+pub mod change;
 pub mod common;
+#[cfg(all(target_arch = "wasm32", any(feature = "yew", feature = "dio")))]
+mod dom;
+pub mod error;
+pub mod history;
+pub mod policy;
+#[cfg(feature = "presets")]
+pub mod presets;
+pub mod registry;
+pub mod resolver;
+pub mod route;
 
 #[cfg(feature = "yew")]
 pub mod yew;
@@ -16,4 +29,16 @@ pub mod dioxus;
 #[cfg(feature = "lep")]
 pub mod leptos;
 
-pub use common::{ColorTokens, CustomTheme, StorageType, Theme};
+pub use axis::ThemeAxis;
+pub use base16::Base16Scheme;
+pub use change::{ChangeReason, ThemeChange};
+pub use common::{
+    ColorTokens, Contrast, CustomTheme, StorageType, Theme, ThemeAttribute, ThemeLookup, ThemeSeed,
+    ThemeTransition, UserPreferences,
+};
+pub use error::{ThemeError, ThemeErrors};
+pub use history::{HistoryEntry, ThemeHistory};
+pub use policy::ThemePolicy;
+pub use registry::{CollisionPolicy, ThemeMeta, ThemeRegistry, ThemeVariant};
+pub use resolver::{ResolveInput, ThemeResolver};
+pub use route::{RouteTheme, ThemedRoute};
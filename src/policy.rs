@@ -0,0 +1,92 @@
+//! Restrictions on the themes users may pick.
+//!
+//! Themes are named by their encoding (`light`, `dark`, `system` or `custom:<name>`), and
+//! custom themes also by their bare name.
+//!
+//! ```
+//! use theme::{Theme, ThemeError, ThemePolicy};
+//!
+//! let policy = ThemePolicy::new().with_denied(["system"]);
+//! assert!(policy.check(&Theme::Dark).is_ok());
+//! assert_eq!(
+//!     policy.check(&Theme::System),
+//!     Err(ThemeError::NotAllowed("system".to_string()))
+//! );
+//!
+//! let locked = policy.with_lock(Theme::Dark);
+//! assert_eq!(
+//!     locked.check(&Theme::Light),
+//!     Err(ThemeError::Locked("dark".to_string()))
+//! );
+//! ```
+
+use crate::common::Theme;
+use crate::error::ThemeError;
+
+/// Which themes users may pick, and optionally the one theme they are locked to.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ThemePolicy {
+    /// Themes users may pick. An empty list allows every theme not denied.
+    pub allowed: Vec<String>,
+    /// Themes users may not pick.
+    pub denied: Vec<String>,
+    /// Theme applied to every user, like `forced_theme` but changeable at runtime.
+    pub lock: Option<Theme>,
+}
+
+impl ThemePolicy {
+    /// Creates a policy allowing every theme.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows only the given themes.
+    pub fn with_allowed<I, S>(mut self, themes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed = themes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Denies the given themes.
+    pub fn with_denied<I, S>(mut self, themes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.denied = themes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Locks every user to `theme`.
+    pub fn with_lock(mut self, theme: Theme) -> Self {
+        self.lock = Some(theme);
+        self
+    }
+
+    /// Returns whether users may pick `theme`, ignoring the lock.
+    pub fn permits(&self, theme: &Theme) -> bool {
+        let listed = |names: &[String]| names.iter().any(|name| names_theme(name, theme));
+        !listed(&self.denied) && (self.allowed.is_empty() || listed(&self.allowed))
+    }
+
+    /// Checks whether a user may select `theme`.
+    ///
+    /// While locked, only the locked theme may be selected.
+    pub fn check(&self, theme: &Theme) -> Result<(), ThemeError> {
+        match &self.lock {
+            Some(lock) if lock != theme => Err(ThemeError::Locked(lock.to_string())),
+            _ if !self.permits(theme) => Err(ThemeError::NotAllowed(theme.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn names_theme(name: &str, theme: &Theme) -> bool {
+    match theme {
+        Theme::Custom(custom) if custom.name == name => true,
+        _ => theme.to_string() == name,
+    }
+}
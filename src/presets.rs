@@ -0,0 +1,245 @@
+//! Curated preset themes, enabled with the `presets` feature.
+//!
+//! Every preset keeps its `text` token at a WCAG AA contrast ratio of at least 4.5:1 against
+//! `background`, and every other token at 3:1 or more. Where an official palette color falls
+//! just short of 3:1 it is darkened by the smallest step that reaches it.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use std::rc::Rc;
+//! use theme::CustomTheme;
+//!
+//! let registry = theme::presets::registry();
+//! assert_eq!(registry.meta("nord").unwrap().scheme, "Nord");
+//!
+//! let custom_themes: HashMap<String, Rc<CustomTheme>> = registry.into();
+//! assert!(custom_themes.contains_key("catppuccin-mocha"));
+//! ```
+
+use crate::common::{ColorTokens, CustomTheme};
+use crate::registry::{ThemeMeta, ThemeRegistry, ThemeVariant};
+
+struct Preset {
+    name: &'static str,
+    scheme: &'static str,
+    author: &'static str,
+    variant: ThemeVariant,
+    /// `primary`, `secondary`, `background`, `text`, `error`, `warning`, `success`.
+    colors: [&'static str; 7],
+}
+
+const PRESETS: &[Preset] = &[
+    Preset {
+        name: "solarized-light",
+        scheme: "Solarized Light",
+        author: "Ethan Schoonover",
+        variant: ThemeVariant::Light,
+        colors: [
+            "#268bd2", "#6c71c4", "#fdf6e3", "#586e75", "#dc322f", "#b18600", "#829600",
+        ],
+    },
+    Preset {
+        name: "solarized-dark",
+        scheme: "Solarized Dark",
+        author: "Ethan Schoonover",
+        variant: ThemeVariant::Dark,
+        colors: [
+            "#268bd2", "#2aa198", "#002b36", "#93a1a1", "#dc322f", "#b58900", "#859900",
+        ],
+    },
+    Preset {
+        name: "nord",
+        scheme: "Nord",
+        author: "Arctic Ice Studio",
+        variant: ThemeVariant::Dark,
+        colors: [
+            "#88c0d0", "#81a1c1", "#2e3440", "#eceff4", "#bf616a", "#ebcb8b", "#a3be8c",
+        ],
+    },
+    Preset {
+        name: "dracula",
+        scheme: "Dracula",
+        author: "Zeno Rocha",
+        variant: ThemeVariant::Dark,
+        colors: [
+            "#bd93f9", "#ff79c6", "#282a36", "#f8f8f2", "#ff5555", "#f1fa8c", "#50fa7b",
+        ],
+    },
+    Preset {
+        name: "gruvbox-light",
+        scheme: "Gruvbox Light",
+        author: "Pavel Pertsev",
+        variant: ThemeVariant::Light,
+        colors: [
+            "#076678", "#8f3f71", "#fbf1c7", "#3c3836", "#9d0006", "#b57614", "#79740e",
+        ],
+    },
+    Preset {
+        name: "gruvbox-dark",
+        scheme: "Gruvbox Dark",
+        author: "Pavel Pertsev",
+        variant: ThemeVariant::Dark,
+        colors: [
+            "#83a598", "#d3869b", "#282828", "#ebdbb2", "#fb4934", "#fabd2f", "#b8bb26",
+        ],
+    },
+    Preset {
+        name: "catppuccin-latte",
+        scheme: "Catppuccin Latte",
+        author: "Catppuccin",
+        variant: ThemeVariant::Light,
+        colors: [
+            "#1e66f5", "#8839ef", "#eff1f5", "#4c4f69", "#d20f39", "#c07a19", "#3f9d2a",
+        ],
+    },
+    Preset {
+        name: "catppuccin-frappe",
+        scheme: "Catppuccin Frappé",
+        author: "Catppuccin",
+        variant: ThemeVariant::Dark,
+        colors: [
+            "#8caaee", "#ca9ee6", "#303446", "#c6d0f5", "#e78284", "#e5c890", "#a6d189",
+        ],
+    },
+    Preset {
+        name: "catppuccin-macchiato",
+        scheme: "Catppuccin Macchiato",
+        author: "Catppuccin",
+        variant: ThemeVariant::Dark,
+        colors: [
+            "#8aadf4", "#c6a0f6", "#24273a", "#cad3f5", "#ed8796", "#eed49f", "#a6da95",
+        ],
+    },
+    Preset {
+        name: "catppuccin-mocha",
+        scheme: "Catppuccin Mocha",
+        author: "Catppuccin",
+        variant: ThemeVariant::Dark,
+        colors: [
+            "#89b4fa", "#cba6f7", "#1e1e2e", "#cdd6f4", "#f38ba8", "#f9e2af", "#a6e3a1",
+        ],
+    },
+    Preset {
+        name: "github-light",
+        scheme: "GitHub Light",
+        author: "GitHub",
+        variant: ThemeVariant::Light,
+        colors: [
+            "#0969da", "#8250df", "#ffffff", "#1f2328", "#cf222e", "#9a6700", "#1a7f37",
+        ],
+    },
+    Preset {
+        name: "github-dark",
+        scheme: "GitHub Dark",
+        author: "GitHub",
+        variant: ThemeVariant::Dark,
+        colors: [
+            "#4493f8", "#ab7df8", "#0d1117", "#e6edf3", "#f85149", "#d29922", "#3fb950",
+        ],
+    },
+];
+
+impl Preset {
+    fn theme(&self) -> CustomTheme {
+        let [
+            primary,
+            secondary,
+            background,
+            text,
+            error,
+            warning,
+            success,
+        ] = self.colors;
+        CustomTheme {
+            name: self.name.to_string(),
+            tokens: ColorTokens {
+                primary: primary.to_string(),
+                secondary: secondary.to_string(),
+                background: background.to_string(),
+                text: text.to_string(),
+                error: Some(error.to_string()),
+                warning: Some(warning.to_string()),
+                success: Some(success.to_string()),
+            },
+            base: None,
+        }
+    }
+
+    fn meta(&self) -> ThemeMeta {
+        ThemeMeta {
+            scheme: self.scheme.to_string(),
+            author: Some(self.author.to_string()),
+            license: Some("MIT".to_string()),
+            variant: Some(self.variant),
+        }
+    }
+}
+
+/// Returns the names of all presets.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    PRESETS.iter().map(|preset| preset.name)
+}
+
+/// Returns the preset called `name`, e.g. `"nord"` or `"catppuccin-mocha"`.
+pub fn get(name: &str) -> Option<CustomTheme> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name == name)
+        .map(Preset::theme)
+}
+
+/// Returns a registry holding every preset along with its metadata.
+pub fn registry() -> ThemeRegistry {
+    let mut registry = ThemeRegistry::new();
+    for preset in PRESETS {
+        registry
+            .insert_with_meta(preset.theme(), preset.meta())
+            .expect("preset names are unique");
+    }
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::contrast_ratio;
+
+    #[test]
+    fn presets_meet_the_documented_contrast_ratios() {
+        for preset in PRESETS {
+            let tokens = get(preset.name).unwrap().tokens;
+            let ratio = |color: &str| contrast_ratio(color, &tokens.background).unwrap();
+
+            assert!(
+                ratio(&tokens.text) >= 4.5,
+                "{} text: {:.2}",
+                preset.name,
+                ratio(&tokens.text)
+            );
+            let others = [
+                ("primary", Some(&tokens.primary)),
+                ("secondary", Some(&tokens.secondary)),
+                ("error", tokens.error.as_ref()),
+                ("warning", tokens.warning.as_ref()),
+                ("success", tokens.success.as_ref()),
+            ];
+            for (field, color) in others {
+                let color = color.unwrap();
+                assert!(
+                    ratio(color) >= 3.0,
+                    "{} {}: {:.2}",
+                    preset.name,
+                    field,
+                    ratio(color)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn presets_are_valid_and_uniquely_named() {
+        let registry = registry();
+        assert_eq!(registry.len(), PRESETS.len());
+        assert!(registry.resolve().is_ok());
+    }
+}
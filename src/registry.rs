@@ -0,0 +1,526 @@
+//! A collection of custom themes that can be loaded from TOML, YAML or JSON.
+//!
+//! Each format is behind a cargo feature of the same name (`toml`, `yaml`, `json`). A document
+//! holds either a single theme, a list of themes, or a `themes` list:
+//!
+//! ```toml
+//! [[themes]]
+//! name = "solarized"
+//!
+//! [themes.tokens]
+//! primary = "#268bd2"
+//! secondary = "#2aa198"
+//! background = "#fdf6e3"
+//! text = "#586e75"
+//! ```
+
+use crate::common::{ColorTokens, CustomTheme, Theme, ThemeLookup};
+use crate::error::{ThemeError, ThemeErrors};
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json"))]
+use serde::Deserialize;
+use std::collections::HashMap;
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json"))]
+use std::path::Path;
+use std::rc::Rc;
+
+/// What a [`ThemeRegistry`] does when a theme is added under a name it already holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollisionPolicy {
+    /// Reject the new theme with an error.
+    #[default]
+    Error,
+    /// Replace the existing theme with the new one.
+    Replace,
+    /// Keep the existing theme and silently drop the new one.
+    KeepExisting,
+}
+
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json"))]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ThemeDocument {
+    Themes { themes: Vec<CustomTheme> },
+    List(Vec<CustomTheme>),
+    Single(CustomTheme),
+}
+
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json"))]
+impl ThemeDocument {
+    fn into_themes(self) -> Vec<CustomTheme> {
+        match self {
+            ThemeDocument::Themes { themes } | ThemeDocument::List(themes) => themes,
+            ThemeDocument::Single(theme) => vec![theme],
+        }
+    }
+}
+
+/// Whether a theme is designed for a light or a dark background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeVariant {
+    Light,
+    Dark,
+}
+
+/// Descriptive metadata about a registered theme.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ThemeMeta {
+    /// Human readable scheme name, e.g. `"Catppuccin Mocha"`.
+    pub scheme: String,
+    /// Author of the original palette.
+    pub author: Option<String>,
+    /// SPDX identifier of the palette's license.
+    pub license: Option<String>,
+    /// Whether the theme is light or dark.
+    pub variant: Option<ThemeVariant>,
+}
+
+/// A named set of custom themes, convertible into the map expected by
+/// `ThemeProviderProps::custom_themes`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ThemeRegistry {
+    themes: HashMap<String, Rc<CustomTheme>>,
+    meta: HashMap<String, ThemeMeta>,
+    policy: CollisionPolicy,
+}
+
+impl ThemeRegistry {
+    /// Creates an empty registry that rejects name collisions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the policy applied when a theme name is already registered.
+    pub fn with_policy(mut self, policy: CollisionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the collision policy of this registry.
+    pub fn policy(&self) -> CollisionPolicy {
+        self.policy
+    }
+
+    /// Adds a theme, applying the collision policy. Returns `true` if the theme was stored.
+    pub fn insert(&mut self, theme: impl Into<Rc<CustomTheme>>) -> Result<bool, ThemeError> {
+        self.insert_entry(theme.into(), None)
+    }
+
+    /// Adds a theme together with its metadata, applying the collision policy.
+    /// Returns `true` if the theme was stored.
+    pub fn insert_with_meta(
+        &mut self,
+        theme: impl Into<Rc<CustomTheme>>,
+        meta: ThemeMeta,
+    ) -> Result<bool, ThemeError> {
+        self.insert_entry(theme.into(), Some(meta))
+    }
+
+    fn insert_entry(
+        &mut self,
+        theme: Rc<CustomTheme>,
+        meta: Option<ThemeMeta>,
+    ) -> Result<bool, ThemeError> {
+        if self.themes.contains_key(&theme.name) {
+            match self.policy {
+                CollisionPolicy::Error => {
+                    return Err(ThemeError::DuplicateTheme(theme.name.clone()));
+                }
+                CollisionPolicy::KeepExisting => return Ok(false),
+                CollisionPolicy::Replace => {}
+            }
+        }
+        match meta {
+            Some(meta) => self.meta.insert(theme.name.clone(), meta),
+            None => self.meta.remove(&theme.name),
+        };
+        self.themes.insert(theme.name.clone(), theme);
+        Ok(true)
+    }
+
+    /// Adds every theme of `other`, applying this registry's collision policy.
+    ///
+    /// With [`CollisionPolicy::Error`] nothing is added if any name collides.
+    pub fn merge(&mut self, other: ThemeRegistry) -> Result<usize, ThemeError> {
+        let ThemeRegistry {
+            themes, mut meta, ..
+        } = other;
+        self.insert_all(themes.into_iter().map(|(name, theme)| {
+            let meta = meta.remove(&name);
+            (theme, meta)
+        }))
+    }
+
+    /// Loads themes from a JSON document. Returns the number of themes stored.
+    #[cfg(feature = "json")]
+    pub fn load_json(&mut self, source: &str) -> Result<usize, ThemeError> {
+        let document: ThemeDocument =
+            serde_json::from_str(source).map_err(|e| ThemeError::Parse {
+                format: "JSON",
+                message: e.to_string(),
+            })?;
+        self.insert_all(
+            document
+                .into_themes()
+                .into_iter()
+                .map(|theme| (Rc::new(theme), None)),
+        )
+    }
+
+    /// Loads themes from a TOML document. Returns the number of themes stored.
+    #[cfg(feature = "toml")]
+    pub fn load_toml(&mut self, source: &str) -> Result<usize, ThemeError> {
+        let document: ThemeDocument = toml::from_str(source).map_err(|e| ThemeError::Parse {
+            format: "TOML",
+            message: e.to_string(),
+        })?;
+        self.insert_all(
+            document
+                .into_themes()
+                .into_iter()
+                .map(|theme| (Rc::new(theme), None)),
+        )
+    }
+
+    /// Loads themes from a YAML document. Returns the number of themes stored.
+    #[cfg(feature = "yaml")]
+    pub fn load_yaml(&mut self, source: &str) -> Result<usize, ThemeError> {
+        let document: ThemeDocument =
+            serde_yaml::from_str(source).map_err(|e| ThemeError::Parse {
+                format: "YAML",
+                message: e.to_string(),
+            })?;
+        self.insert_all(
+            document
+                .into_themes()
+                .into_iter()
+                .map(|theme| (Rc::new(theme), None)),
+        )
+    }
+
+    /// Loads themes from a file, picking the format from its extension
+    /// (`.toml`, `.yaml`, `.yml` or `.json`). Returns the number of themes stored.
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json"))]
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<usize, ThemeError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|e| ThemeError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            #[cfg(feature = "toml")]
+            Some("toml") => self.load_toml(&source),
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => self.load_yaml(&source),
+            #[cfg(feature = "json")]
+            Some("json") => self.load_json(&source),
+            _ => Err(ThemeError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    /// Returns the theme registered under `name`.
+    pub fn get(&self, name: &str) -> Option<&Rc<CustomTheme>> {
+        self.themes.get(name)
+    }
+
+    /// Removes and returns the theme registered under `name`.
+    pub fn remove(&mut self, name: &str) -> Option<Rc<CustomTheme>> {
+        self.meta.remove(name);
+        self.themes.remove(name)
+    }
+
+    /// Returns the metadata of the theme registered under `name`, if any was provided.
+    pub fn meta(&self, name: &str) -> Option<&ThemeMeta> {
+        self.meta.get(name)
+    }
+
+    /// Returns `true` if a theme is registered under `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.themes.contains_key(name)
+    }
+
+    /// Returns the number of registered themes.
+    pub fn len(&self) -> usize {
+        self.themes.len()
+    }
+
+    /// Returns `true` if no theme is registered.
+    pub fn is_empty(&self) -> bool {
+        self.themes.is_empty()
+    }
+
+    /// Returns the registered theme names in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.themes.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Parses a theme encoding, resolving custom themes against this registry.
+    pub fn parse(&self, s: &str) -> Result<Theme, ThemeError> {
+        Theme::parse_with(s, self)
+    }
+
+    /// Iterates over the registered themes in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &Rc<CustomTheme>> {
+        self.themes.values()
+    }
+
+    /// Validates every theme and resolves every `base` reference, returning the composed
+    /// tokens of each theme.
+    ///
+    /// All problems are reported at once, in alphabetical order of theme name.
+    pub fn resolve(&self) -> Result<HashMap<String, ColorTokens>, ThemeErrors> {
+        let mut resolved = HashMap::with_capacity(self.themes.len());
+        let mut errors = ThemeErrors::new();
+
+        for name in self.names() {
+            let theme = &self.themes[name];
+            if let Err(theme_errors) = theme.validate() {
+                errors.extend(theme_errors.into_iter().map(|e| e.in_theme(name)));
+            }
+            match theme.compose_with_base(&self.themes) {
+                Ok(tokens) => {
+                    resolved.insert(name.to_string(), tokens);
+                }
+                Err(error) => errors.push(error.in_theme(name)),
+            }
+        }
+
+        errors.into_result(resolved)
+    }
+
+    /// Returns the themes as the map expected by `ThemeProviderProps::custom_themes`.
+    pub fn into_map(self) -> HashMap<String, Rc<CustomTheme>> {
+        self.themes
+    }
+
+    fn insert_all(
+        &mut self,
+        entries: impl IntoIterator<Item = (Rc<CustomTheme>, Option<ThemeMeta>)>,
+    ) -> Result<usize, ThemeError> {
+        let entries: Vec<_> = entries.into_iter().collect();
+
+        if self.policy == CollisionPolicy::Error {
+            let mut seen = std::collections::HashSet::new();
+            for (theme, _) in &entries {
+                if self.themes.contains_key(&theme.name) || !seen.insert(theme.name.as_str()) {
+                    return Err(ThemeError::DuplicateTheme(theme.name.clone()));
+                }
+            }
+        }
+
+        let mut stored = 0;
+        for (theme, meta) in entries {
+            if self.insert_entry(theme, meta)? {
+                stored += 1;
+            }
+        }
+        Ok(stored)
+    }
+}
+
+impl ThemeLookup for ThemeRegistry {
+    fn lookup_theme(&self, name: &str) -> Option<Rc<CustomTheme>> {
+        self.themes.get(name).cloned()
+    }
+}
+
+impl From<ThemeRegistry> for HashMap<String, Rc<CustomTheme>> {
+    fn from(registry: ThemeRegistry) -> Self {
+        registry.into_map()
+    }
+}
+
+impl From<HashMap<String, Rc<CustomTheme>>> for ThemeRegistry {
+    fn from(themes: HashMap<String, Rc<CustomTheme>>) -> Self {
+        ThemeRegistry {
+            themes,
+            ..Default::default()
+        }
+    }
+}
+
+impl FromIterator<CustomTheme> for ThemeRegistry {
+    /// Collects themes into a registry. Later themes replace earlier ones with the same name.
+    fn from_iter<I: IntoIterator<Item = CustomTheme>>(iter: I) -> Self {
+        let themes = iter
+            .into_iter()
+            .map(|theme| (theme.name.clone(), Rc::new(theme)))
+            .collect();
+        ThemeRegistry {
+            themes,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::custom;
+
+    #[test]
+    fn error_policy_rejects_a_duplicate() {
+        let mut registry = ThemeRegistry::new();
+        assert_eq!(registry.insert(custom("brand", None, "#000001")), Ok(true));
+        assert_eq!(
+            registry.insert(custom("brand", None, "#000002")),
+            Err(ThemeError::DuplicateTheme("brand".to_string()))
+        );
+        assert_eq!(registry.get("brand").unwrap().tokens.primary, "#000001");
+    }
+
+    #[test]
+    fn replace_policy_overwrites_the_theme_and_its_meta() {
+        let mut registry = ThemeRegistry::new().with_policy(CollisionPolicy::Replace);
+        let meta = ThemeMeta {
+            scheme: "Brand".to_string(),
+            ..ThemeMeta::default()
+        };
+        registry
+            .insert_with_meta(custom("brand", None, "#000001"), meta)
+            .unwrap();
+        assert_eq!(registry.insert(custom("brand", None, "#000002")), Ok(true));
+
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.get("brand").unwrap().tokens.primary, "#000002");
+        assert_eq!(registry.meta("brand"), None);
+    }
+
+    #[test]
+    fn keep_existing_policy_drops_the_new_theme() {
+        let mut registry = ThemeRegistry::new().with_policy(CollisionPolicy::KeepExisting);
+        registry.insert(custom("brand", None, "#000001")).unwrap();
+        assert_eq!(registry.insert(custom("brand", None, "#000002")), Ok(false));
+        assert_eq!(registry.get("brand").unwrap().tokens.primary, "#000001");
+    }
+
+    #[test]
+    fn merge_applies_the_policy_of_the_receiving_registry() {
+        let other: ThemeRegistry = [
+            (*custom("ocean", None, "#000001")).clone(),
+            (*custom("brand", None, "#000002")).clone(),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut strict = ThemeRegistry::new();
+        strict.insert(custom("brand", None, "#000003")).unwrap();
+        assert_eq!(
+            strict.merge(other.clone()),
+            Err(ThemeError::DuplicateTheme("brand".to_string()))
+        );
+        assert_eq!(strict.names(), ["brand"]);
+
+        let mut lenient = ThemeRegistry::new().with_policy(CollisionPolicy::KeepExisting);
+        lenient.insert(custom("brand", None, "#000003")).unwrap();
+        assert_eq!(lenient.merge(other), Ok(1));
+        assert_eq!(lenient.names(), ["brand", "ocean"]);
+        assert_eq!(lenient.get("brand").unwrap().tokens.primary, "#000003");
+    }
+
+    #[test]
+    fn resolve_collects_every_error() {
+        let mut broken = (*custom("broken", Some("nowhere"), "red")).clone();
+        broken.tokens.text = "black".to_string();
+        let registry: ThemeRegistry = [
+            (*custom("fine", Some("dark"), "#000001")).clone(),
+            broken,
+            (*custom("loop", Some("loop"), "#000002")).clone(),
+        ]
+        .into_iter()
+        .collect();
+
+        let errors = registry.resolve().unwrap_err();
+        assert_eq!(
+            errors.errors(),
+            [
+                ThemeError::InvalidColor {
+                    field: "primary".to_string(),
+                    value: "red".to_string(),
+                }
+                .in_theme("broken"),
+                ThemeError::InvalidColor {
+                    field: "text".to_string(),
+                    value: "black".to_string(),
+                }
+                .in_theme("broken"),
+                ThemeError::MissingBase {
+                    base: "nowhere".to_string(),
+                    chain: vec!["broken".to_string(), "nowhere".to_string()],
+                }
+                .in_theme("broken"),
+                ThemeError::InheritanceCycle {
+                    chain: vec!["loop".to_string(), "loop".to_string()],
+                }
+                .in_theme("loop"),
+            ]
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn loads_json() {
+        let mut registry = ThemeRegistry::new();
+        let source = r##"[
+            {"name": "ocean", "base": null, "tokens": {"primary": "#0077be", "secondary": "#00a0b0", "background": "#f0f8ff", "text": "#102030"}},
+            {"name": "forest", "base": "ocean", "tokens": {"primary": "#228b22", "secondary": "#2e8b57", "background": "#f5fff5", "text": "#0b1f0b"}}
+        ]"##;
+        assert_eq!(registry.load_json(source), Ok(2));
+        assert_eq!(
+            registry.get("forest").unwrap().base.as_deref(),
+            Some("ocean")
+        );
+        assert!(matches!(
+            registry.load_json("{"),
+            Err(ThemeError::Parse { format: "JSON", .. })
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn loads_toml() {
+        let mut registry = ThemeRegistry::new();
+        let source = r##"
+            [[themes]]
+            name = "solarized"
+
+            [themes.tokens]
+            primary = "#268bd2"
+            secondary = "#2aa198"
+            background = "#fdf6e3"
+            text = "#586e75"
+        "##;
+        assert_eq!(registry.load_toml(source), Ok(1));
+        assert_eq!(registry.get("solarized").unwrap().tokens.primary, "#268bd2");
+        assert_eq!(
+            registry.load_toml(source),
+            Err(ThemeError::DuplicateTheme("solarized".to_string()))
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn loads_yaml() {
+        let mut registry = ThemeRegistry::new();
+        let source = r##"
+name: mono
+tokens:
+  primary: "#111111"
+  secondary: "#333333"
+  background: "#ffffff"
+  text: "#000000"
+  error: "#aa0000"
+"##;
+        assert_eq!(registry.load_yaml(source), Ok(1));
+        assert_eq!(
+            registry.get("mono").unwrap().tokens.error.as_deref(),
+            Some("#aa0000")
+        );
+    }
+}
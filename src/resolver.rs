@@ -0,0 +1,279 @@
+//! The pipeline deciding which theme a provider applies.
+//!
+//! A provider asks each [`ThemeResolver`] of its `resolvers` prop in turn, and the first one
+//! that returns a theme wins. Drop a resolver from the list to disable it, reorder the list, or
+//! replace an entry with [`ThemeResolver::custom`].
+//!
+//! ```
+//! use theme::{ResolveInput, Theme, ThemeResolver};
+//!
+//! let mut resolvers = ThemeResolver::defaults();
+//! resolvers.insert(4, ThemeResolver::schedule());
+//!
+//! let input = ResolveInput {
+//!     choice: Some(Theme::System),
+//!     hour: Some(22),
+//!     ..ResolveInput::default()
+//! };
+//! assert_eq!(input.resolve(&resolvers), (Theme::Dark, "schedule"));
+//!
+//! let weekend = ThemeResolver::custom("weekend", |_| Some(Theme::Light));
+//! assert_eq!(input.resolve(&[weekend]), (Theme::Light, "weekend"));
+//! ```
+
+use crate::common::Theme;
+use crate::route::RouteTheme;
+use std::fmt;
+use std::rc::Rc;
+
+/// What a provider knows when resolving its theme.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResolveInput {
+    /// The `forced_theme` prop.
+    pub forced: Option<Theme>,
+    /// The theme locked by the provider's `ThemePolicy`.
+    pub locked: Option<Theme>,
+    /// The theme asked for by the current route.
+    pub route: RouteTheme,
+    /// The theme being previewed.
+    pub preview: Option<Theme>,
+    /// The theme the user chose, if they chose one.
+    pub choice: Option<Theme>,
+    /// The `default_theme` prop.
+    pub default: Theme,
+    /// The theme preferred by the operating system.
+    pub system: Theme,
+    /// The resolved theme of the enclosing provider, if this one is nested.
+    pub parent: Option<Theme>,
+    /// The local hour, from 0 to 23, when known.
+    pub hour: Option<u32>,
+}
+
+impl ResolveInput {
+    /// Returns the theme selected by the user, the route or `default_theme`, in that order.
+    pub fn selection(&self) -> Theme {
+        let selected = self.choice.clone().unwrap_or_else(|| self.default.clone());
+        match &self.route {
+            // A forced route is applied by `ThemeResolver::Route`, not through the selection.
+            RouteTheme::Forced(_) => selected,
+            route => route.select(selected, self.choice.is_some()),
+        }
+    }
+
+    /// Returns the theme `Theme::System` stands for: the parent's theme in a nested provider,
+    /// and the operating system's preference otherwise.
+    pub fn automatic(&self) -> Theme {
+        self.parent.clone().unwrap_or_else(|| self.system.clone())
+    }
+
+    /// Runs `resolvers` in order, returning the first theme produced with the name of the
+    /// resolver that produced it.
+    ///
+    /// When none produces a theme, the selection is used, reported as `default`.
+    pub fn resolve(&self, resolvers: &[ThemeResolver]) -> (Theme, &'static str) {
+        resolvers
+            .iter()
+            .find_map(|resolver| resolver.resolve(self).map(|theme| (theme, resolver.name())))
+            .unwrap_or_else(|| (self.fallback(), ThemeResolver::Default.name()))
+    }
+
+    fn fallback(&self) -> Theme {
+        match self.selection() {
+            Theme::System => self.automatic(),
+            other => other,
+        }
+    }
+}
+
+/// A resolver function, given the provider's state and returning a theme or `None` to pass.
+pub type ResolveFn = Rc<dyn Fn(&ResolveInput) -> Option<Theme>>;
+
+/// One step of theme resolution.
+#[derive(Clone)]
+pub enum ThemeResolver {
+    /// The `forced_theme` prop, then the theme locked by the `ThemePolicy`.
+    Policy,
+    /// A theme forced by the current route.
+    Route,
+    /// The theme being previewed.
+    Preview,
+    /// The user's choice, unless it is `Theme::System`.
+    User,
+    /// Light from `light_from` until `dark_from`, dark otherwise, while the selection is
+    /// `Theme::System`.
+    Schedule { light_from: u32, dark_from: u32 },
+    /// The operating system's preference, or the parent's theme, while the selection is
+    /// `Theme::System`.
+    System,
+    /// The route's default or `default_theme`, following the system for `Theme::System`.
+    Default,
+    /// A resolver of your own.
+    Custom {
+        name: &'static str,
+        resolve: ResolveFn,
+    },
+}
+
+impl ThemeResolver {
+    /// The pipeline used by default: policy, route, preview, user, system and default.
+    pub fn defaults() -> Vec<ThemeResolver> {
+        vec![
+            ThemeResolver::Policy,
+            ThemeResolver::Route,
+            ThemeResolver::Preview,
+            ThemeResolver::User,
+            ThemeResolver::System,
+            ThemeResolver::Default,
+        ]
+    }
+
+    /// A schedule using the light theme from 7 AM until 7 PM.
+    pub fn schedule() -> Self {
+        ThemeResolver::Schedule {
+            light_from: 7,
+            dark_from: 19,
+        }
+    }
+
+    /// Creates a resolver named `name` from a function.
+    pub fn custom(
+        name: &'static str,
+        resolve: impl Fn(&ResolveInput) -> Option<Theme> + 'static,
+    ) -> Self {
+        ThemeResolver::Custom {
+            name,
+            resolve: Rc::new(resolve),
+        }
+    }
+
+    /// Returns the name reported when this resolver produces the theme.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeResolver::Policy => "policy",
+            ThemeResolver::Route => "route",
+            ThemeResolver::Preview => "preview",
+            ThemeResolver::User => "user",
+            ThemeResolver::Schedule { .. } => "schedule",
+            ThemeResolver::System => "system",
+            ThemeResolver::Default => "default",
+            ThemeResolver::Custom { name, .. } => name,
+        }
+    }
+
+    /// Returns the theme this resolver produces for `input`, or `None` to pass.
+    pub fn resolve(&self, input: &ResolveInput) -> Option<Theme> {
+        match self {
+            ThemeResolver::Policy => input.forced.clone().or_else(|| input.locked.clone()),
+            ThemeResolver::Route => input.route.forced().cloned(),
+            ThemeResolver::Preview => input.preview.clone(),
+            ThemeResolver::User => input
+                .choice
+                .clone()
+                .filter(|choice| *choice != Theme::System),
+            ThemeResolver::Schedule {
+                light_from,
+                dark_from,
+            } => {
+                let hour = input.hour.filter(|_| input.selection() == Theme::System)?;
+                Some(if (*light_from..*dark_from).contains(&hour) {
+                    Theme::Light
+                } else {
+                    Theme::Dark
+                })
+            }
+            ThemeResolver::System => {
+                (input.selection() == Theme::System).then(|| input.automatic())
+            }
+            ThemeResolver::Default => Some(input.fallback()),
+            ThemeResolver::Custom { resolve, .. } => resolve(input),
+        }
+    }
+}
+
+impl PartialEq for ThemeResolver {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                ThemeResolver::Custom { name, resolve },
+                ThemeResolver::Custom {
+                    name: other_name,
+                    resolve: other_resolve,
+                },
+            ) => name == other_name && Rc::ptr_eq(resolve, other_resolve),
+            (
+                ThemeResolver::Schedule {
+                    light_from,
+                    dark_from,
+                },
+                ThemeResolver::Schedule {
+                    light_from: other_light_from,
+                    dark_from: other_dark_from,
+                },
+            ) => light_from == other_light_from && dark_from == other_dark_from,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl fmt::Debug for ThemeResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeResolver::Schedule {
+                light_from,
+                dark_from,
+            } => f
+                .debug_struct("Schedule")
+                .field("light_from", light_from)
+                .field("dark_from", dark_from)
+                .finish(),
+            ThemeResolver::Custom { name, .. } => {
+                f.debug_struct("Custom").field("name", name).finish()
+            }
+            other => f.write_str(match other {
+                ThemeResolver::Policy => "Policy",
+                ThemeResolver::Route => "Route",
+                ThemeResolver::Preview => "Preview",
+                ThemeResolver::User => "User",
+                ThemeResolver::System => "System",
+                _ => "Default",
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_default_applies_until_the_user_chooses() {
+        let resolvers = ThemeResolver::defaults();
+        let input = ResolveInput {
+            route: RouteTheme::Default(Theme::Dark),
+            default: Theme::Light,
+            ..ResolveInput::default()
+        };
+        assert_eq!(input.resolve(&resolvers), (Theme::Dark, "default"));
+
+        let chosen = ResolveInput {
+            choice: Some(Theme::Light),
+            ..input
+        };
+        assert_eq!(chosen.resolve(&resolvers), (Theme::Light, "user"));
+    }
+
+    #[test]
+    fn forced_route_leaves_the_selection_alone() {
+        let input = ResolveInput {
+            route: RouteTheme::Forced(Theme::Dark),
+            choice: Some(Theme::System),
+            system: Theme::Light,
+            ..ResolveInput::default()
+        };
+        assert_eq!(input.selection(), Theme::System);
+        assert_eq!(
+            input.resolve(&ThemeResolver::defaults()),
+            (Theme::Dark, "route")
+        );
+    }
+}
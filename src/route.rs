@@ -0,0 +1,91 @@
+//! Per-route themes for router-driven apps.
+//!
+//! Implement [`ThemedRoute`] on the route enum of `yew-router` or the Dioxus router, and pass
+//! the current route to `use_route_theme` below the `ThemeProvider`. Routes can force a theme
+//! or supply a default, and the user's own choice comes back once they navigate away.
+//!
+//! ```
+//! use theme::{RouteTheme, Theme, ThemedRoute};
+//!
+//! enum Route {
+//!     Pricing,
+//!     Docs,
+//! }
+//!
+//! impl ThemedRoute for Route {
+//!     fn route_theme(&self) -> RouteTheme {
+//!         match self {
+//!             Route::Pricing => RouteTheme::Forced(Theme::Dark),
+//!             Route::Docs => RouteTheme::Inherit,
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Route::Pricing.route_theme().select(Theme::Light, true), Theme::Dark);
+//! assert_eq!(Route::Docs.route_theme().select(Theme::Light, true), Theme::Light);
+//! ```
+
+use crate::common::Theme;
+
+/// The theme a route asks for.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum RouteTheme {
+    /// Follows the user's choice.
+    #[default]
+    Inherit,
+    /// Uses this theme whatever the user chose.
+    Forced(Theme),
+    /// Uses this theme until the user chooses one.
+    Default(Theme),
+}
+
+impl RouteTheme {
+    /// Returns the theme this route forces, if any.
+    pub fn forced(&self) -> Option<&Theme> {
+        match self {
+            RouteTheme::Forced(theme) => Some(theme),
+            _ => None,
+        }
+    }
+
+    /// Returns the theme to use on this route, given the user's `selected` theme and whether
+    /// they chose it rather than getting the provider's default.
+    pub fn select(&self, selected: Theme, user_chosen: bool) -> Theme {
+        match self {
+            RouteTheme::Inherit => selected,
+            RouteTheme::Forced(theme) => theme.clone(),
+            RouteTheme::Default(theme) if !user_chosen => theme.clone(),
+            RouteTheme::Default(_) => selected,
+        }
+    }
+}
+
+/// A route that declares the theme it is shown in.
+pub trait ThemedRoute {
+    /// The theme this route asks for, [`RouteTheme::Inherit`] by default.
+    fn route_theme(&self) -> RouteTheme {
+        RouteTheme::Inherit
+    }
+}
+
+/// No route, as returned by `yew-router` outside of a matching route, follows the user.
+impl<R: ThemedRoute> ThemedRoute for Option<R> {
+    fn route_theme(&self) -> RouteTheme {
+        self.as_ref()
+            .map(ThemedRoute::route_theme)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_route_yields_to_a_chosen_theme() {
+        let route = RouteTheme::Default(Theme::Dark);
+        assert_eq!(route.select(Theme::Light, false), Theme::Dark);
+        assert_eq!(route.select(Theme::Light, true), Theme::Light);
+        assert_eq!(route.forced(), None);
+    }
+}
@@ -1,22 +1,120 @@
 #![doc = include_str!("../YEW.md")]
 
-use crate::common::{CustomTheme, DEFAULT_STORAGE_KEY, StorageType, Theme};
+pub mod components;
+
+use crate::axis::{ThemeAxis, apply_accent, initial_values};
+use crate::change::{ChangeReason, ThemeChange};
+use crate::common::{
+    ColorTokens, Contrast, CustomTheme, DEFAULT_STORAGE_KEY, DISABLE_TRANSITIONS_CSS, StorageType,
+    Theme, ThemeAttribute, ThemeTransition, UserPreferences,
+};
+use crate::error::ThemeError;
+use crate::history::{DEFAULT_HISTORY_LIMIT, HistoryEntry, ThemeHistory};
+use crate::policy::ThemePolicy;
+use crate::resolver::{ResolveInput, ThemeResolver};
+use crate::route::{RouteTheme, ThemedRoute};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 use yew::prelude::*;
 
+pub use components::{ThemeCycleButton, ThemeSelect, ThemeToggle};
+
 #[derive(Clone, PartialEq)]
 pub struct ThemeContext {
     pub theme: UseStateHandle<Theme>,
     pub resolved_theme: UseStateHandle<Theme>,
     pub system_theme: UseStateHandle<Theme>,
+    pub contrast: UseStateHandle<Contrast>,
+    pub forced_colors: UseStateHandle<bool>,
+    pub preferences: UseStateHandle<UserPreferences>,
     pub set_theme: Callback<Theme>,
+    /// Sets the theme, animating the switch from an origin such as the click point.
+    pub set_theme_at: Callback<(Theme, (f64, f64))>,
+    /// Sets the theme, or returns why the `ThemePolicy` rejects it.
+    pub try_set_theme: Callback<Theme, Result<(), ThemeError>>,
     pub forced_theme: Option<Theme>,
     pub custom_themes: UseStateHandle<HashMap<String, Rc<CustomTheme>>>,
     pub set_custom_theme: Callback<Rc<CustomTheme>>,
     pub reset_to_system: Callback<()>,
     pub preview_theme: UseStateHandle<Option<Theme>>,
     pub apply_preview: Callback<Theme>,
+    /// Ends the preview and returns to the selected theme.
+    pub cancel_preview: Callback<()>,
+    /// Ends the preview by selecting the previewed theme.
+    pub commit_preview: Callback<()>,
+    pub axes: UseStateHandle<HashMap<String, String>>,
+    pub set_axis: Callback<(String, String)>,
+    /// Theme asked for by the current route, see [`use_route_theme`].
+    pub route_theme: UseStateHandle<RouteTheme>,
+    pub set_route_theme: Callback<RouteTheme>,
+    /// Name of the resolver that produced `resolved_theme`, such as `user` or `system`.
+    pub resolved_by: UseStateHandle<&'static str>,
+    pub policy: UseStateHandle<ThemePolicy>,
+    pub set_policy: Callback<ThemePolicy>,
+    /// Locks every user to a theme, or lifts the lock with `None`.
+    pub set_lock: Callback<Option<Theme>>,
+    /// Reverts the latest recorded theme change.
+    pub undo: Callback<()>,
+    /// Applies again the latest undone theme change.
+    pub redo: Callback<()>,
+    pub can_undo: bool,
+    pub can_redo: bool,
+    /// Context of the nearest enclosing provider, if this one is nested.
+    pub parent: Option<Rc<ThemeContext>>,
+}
+
+/// The state of one provider that its color tokens are computed from.
+type ColorInputs = (
+    Theme,
+    UseStateHandle<HashMap<String, Rc<CustomTheme>>>,
+    UseStateHandle<HashMap<String, String>>,
+);
+
+impl ThemeContext {
+    /// Returns the color tokens of the resolved theme with the accent axis applied, or the CSS
+    /// system colors while the browser is in forced colors mode.
+    ///
+    /// In a nested provider the tokens are merged onto the parent's.
+    pub fn colors(&self) -> ColorTokens {
+        if *self.forced_colors {
+            return ColorTokens::system_colors();
+        }
+        let tokens = apply_accent(
+            self.resolved_theme.colors(Some(&self.custom_themes)),
+            &self.axes,
+        );
+        match &self.parent {
+            Some(parent) => parent.colors().merge_with(&tokens),
+            None => tokens,
+        }
+    }
+
+    /// Returns the value in effect on the axis called `name`.
+    pub fn axis(&self, name: &str) -> Option<String> {
+        self.axes.get(name).cloned()
+    }
+
+    /// Returns the state `colors()` is computed from, across this provider and its ancestors.
+    fn color_inputs(&self) -> (bool, Vec<ColorInputs>) {
+        let mut inputs = Vec::new();
+        let mut context = Some(self);
+        while let Some(current) = context {
+            inputs.push((
+                (*current.resolved_theme).clone(),
+                current.custom_themes.clone(),
+                current.axes.clone(),
+            ));
+            context = current.parent.as_deref();
+        }
+        (*self.forced_colors, inputs)
+    }
+
+    /// Returns a setter for the axis called `name`.
+    pub fn axis_setter(&self, name: impl Into<String>) -> Callback<String> {
+        let name = name.into();
+        self.set_axis.reform(move |value| (name.clone(), value))
+    }
 }
 
 #[derive(Properties, Clone, PartialEq)]
@@ -33,6 +131,38 @@ pub struct ThemeProviderProps {
     pub forced_theme: Option<Theme>,
     #[prop_or_default]
     pub custom_themes: HashMap<String, Rc<CustomTheme>>,
+    #[prop_or_default]
+    pub derive_contrast_themes: bool,
+    #[prop_or_default]
+    pub emit_css_variables: bool,
+    #[prop_or(true)]
+    pub respect_forced_colors: bool,
+    #[prop_or_default]
+    pub axes: Vec<ThemeAxis>,
+    #[prop_or_default]
+    pub scoped: bool,
+    #[prop_or_default]
+    pub attribute: ThemeAttribute,
+    #[prop_or_default]
+    pub attribute_values: HashMap<String, String>,
+    #[prop_or_default]
+    pub disable_transitions: bool,
+    #[prop_or(DISABLE_TRANSITIONS_CSS)]
+    pub transition_rule: &'static str,
+    #[prop_or_default]
+    pub transition: ThemeTransition,
+    #[prop_or_default]
+    pub preview_timeout_ms: Option<u32>,
+    #[prop_or_else(ThemeResolver::defaults)]
+    pub resolvers: Vec<ThemeResolver>,
+    #[prop_or_default]
+    pub policy: ThemePolicy,
+    #[prop_or_default]
+    pub on_change: Callback<ThemeChange>,
+    #[prop_or(DEFAULT_HISTORY_LIMIT)]
+    pub history_limit: usize,
+    #[prop_or_default]
+    pub persist_history: bool,
 }
 
 #[function_component(ThemeProvider)]
@@ -50,35 +180,148 @@ pub fn theme_provider(props: &ThemeProviderProps) -> Html {
             storage_name: _,
         forced_theme,
         custom_themes,
+        #[cfg(target_arch = "wasm32")]
+        derive_contrast_themes,
+        #[cfg(not(target_arch = "wasm32"))]
+            derive_contrast_themes: _,
+        #[cfg(target_arch = "wasm32")]
+        emit_css_variables,
+        #[cfg(not(target_arch = "wasm32"))]
+            emit_css_variables: _,
+        #[cfg(target_arch = "wasm32")]
+        respect_forced_colors,
+        #[cfg(not(target_arch = "wasm32"))]
+            respect_forced_colors: _,
+        axes,
+        scoped,
+        #[cfg(target_arch = "wasm32")]
+        attribute,
+        #[cfg(not(target_arch = "wasm32"))]
+            attribute: _,
+        #[cfg(target_arch = "wasm32")]
+        attribute_values,
+        #[cfg(not(target_arch = "wasm32"))]
+            attribute_values: _,
+        #[cfg(target_arch = "wasm32")]
+        disable_transitions,
+        #[cfg(not(target_arch = "wasm32"))]
+            disable_transitions: _,
+        #[cfg(target_arch = "wasm32")]
+        transition_rule,
+        #[cfg(not(target_arch = "wasm32"))]
+            transition_rule: _,
+        #[cfg(target_arch = "wasm32")]
+        transition,
+        #[cfg(not(target_arch = "wasm32"))]
+            transition: _,
+        #[cfg(target_arch = "wasm32")]
+        preview_timeout_ms,
+        #[cfg(not(target_arch = "wasm32"))]
+            preview_timeout_ms: _,
+        resolvers,
+        policy,
+        on_change,
+        history_limit,
+        #[cfg(target_arch = "wasm32")]
+        persist_history,
+        #[cfg(not(target_arch = "wasm32"))]
+            persist_history: _,
     } = props.clone();
 
-    let theme: UseStateHandle<Theme> = {
-        #[cfg(target_arch = "wasm32")]
-        let stored_theme = {
-            use std::str::FromStr;
-            use web_sys::window;
-
-            let window = window().expect("no window");
-            let storage: Option<web_sys::Storage> = match storage_type {
-                StorageType::LocalStorage => window.local_storage().unwrap_or(None),
-                StorageType::SessionStorage => window.session_storage().unwrap_or(None),
-            };
-            storage
-                .and_then(|s| s.get_item(storage_name).ok().flatten())
-                .and_then(|s| Theme::from_str(&s).ok())
-                .unwrap_or(default_theme.clone())
-        };
+    let parent = use_context::<Rc<ThemeContext>>();
+    let parent_theme = parent
+        .as_ref()
+        .map(|parent| (*parent.resolved_theme).clone());
+    let scope_ref = use_node_ref();
+    let is_root = parent.is_none() && !scoped;
 
-        #[cfg(not(target_arch = "wasm32"))]
-        let stored_theme = default_theme.clone();
+    #[cfg(target_arch = "wasm32")]
+    let stored_theme = {
+        use web_sys::window;
 
-        use_state(|| stored_theme)
+        let window = window().expect("no window");
+        let storage: Option<web_sys::Storage> = match storage_type {
+            StorageType::LocalStorage => window.local_storage().unwrap_or(None),
+            StorageType::SessionStorage => window.session_storage().unwrap_or(None),
+        };
+        storage
+            .filter(|_| !scoped)
+            .and_then(|s| s.get_item(storage_name).ok().flatten())
+            .and_then(|s| Theme::parse_with(&s, &custom_themes).ok())
+            .filter(|stored| policy.permits(stored))
     };
+    #[cfg(target_arch = "wasm32")]
+    let has_stored_theme = stored_theme.is_some();
+    #[cfg(target_arch = "wasm32")]
+    let stored_theme = stored_theme.unwrap_or(default_theme.clone());
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let has_stored_theme = false;
+    #[cfg(not(target_arch = "wasm32"))]
+    let stored_theme = default_theme.clone();
+
+    // A theme set through the global store before the root provider mounted wins.
+    let global = is_root
+        .then(global_theme)
+        .flatten()
+        .filter(|global| policy.permits(global));
+
+    // Whether the user chose the selected theme, rather than it coming from `default_theme`.
+    let user_chosen = use_mut_ref(|| has_stored_theme || global.is_some());
+    let theme: UseStateHandle<Theme> = use_state(|| global.unwrap_or(stored_theme));
 
     let system_theme = use_state(|| Theme::Light);
+    let contrast = use_state(Contrast::default);
+    let forced_colors = use_state(|| false);
+    let preferences = use_state(UserPreferences::default);
     let resolved_theme = use_state(|| Theme::Light);
-    let custom_themes_state = use_state(|| custom_themes);
+    let resolved_by = use_state(|| ThemeResolver::Default.name());
+    let custom_themes_state = use_state(|| {
+        let mut themes = parent
+            .as_ref()
+            .map(|parent| (*parent.custom_themes).clone())
+            .unwrap_or_default();
+        themes.extend(custom_themes.clone());
+        themes
+    });
     let preview_theme = use_state(|| None::<Theme>);
+    // The preview in effect, readable by callbacks created before it was set.
+    let active_preview = use_mut_ref(|| None::<Theme>);
+    let route_theme = use_state(RouteTheme::default);
+    let active_route_theme = use_mut_ref(RouteTheme::default);
+    let policy_state = use_state(|| policy.clone());
+    let active_policy = use_mut_ref(|| policy.clone());
+    let axis_values = use_mut_ref(|| {
+        #[cfg(target_arch = "wasm32")]
+        let stored = |key: &str| {
+            crate::dom::storage(storage_type)
+                .filter(|_| !scoped)
+                .and_then(|s| s.get_item(key).ok().flatten())
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let stored = |_: &str| None;
+        initial_values(&axes, stored)
+    });
+    let axes_state = use_state(|| axis_values.borrow().clone());
+    let history = use_state(|| {
+        #[cfg(target_arch = "wasm32")]
+        let history = crate::dom::storage(storage_type)
+            .filter(|_| persist_history && !scoped)
+            .and_then(|s| {
+                s.get_item(&ThemeHistory::storage_key(storage_name))
+                    .ok()
+                    .flatten()
+            })
+            .map(|encoded| ThemeHistory::decode(&encoded, history_limit, &custom_themes))
+            .unwrap_or_else(|| ThemeHistory::new(history_limit));
+        #[cfg(not(target_arch = "wasm32"))]
+        let history = ThemeHistory::new(history_limit);
+        history
+    });
+    // The history including changes made since the last render.
+    let active_history = use_mut_ref(|| (*history).clone());
+    // The selected and resolved themes last reported to `on_change`.
+    let last_change = use_mut_ref(|| None::<(Theme, Theme)>);
 
     #[cfg(target_arch = "wasm32")]
     let html_element: UseStateHandle<web_sys::HtmlElement> = use_state(|| {
@@ -101,16 +344,39 @@ pub fn theme_provider(props: &ThemeProviderProps) -> Html {
         #[cfg(target_arch = "wasm32")]
         let system_theme = system_theme.clone();
         let resolved_theme = resolved_theme.clone();
+        let resolved_by = resolved_by.clone();
+        let resolvers = resolvers.clone();
+        let default_theme = default_theme.clone();
         #[cfg(target_arch = "wasm32")]
         let html_element = html_element.clone();
         #[cfg(target_arch = "wasm32")]
+        let scope_ref = scope_ref.clone();
+        #[cfg(target_arch = "wasm32")]
+        let parent = parent.clone();
+        let parent_theme = parent_theme.clone();
         let forced_theme = forced_theme.clone();
+        let active_preview = active_preview.clone();
+        let active_route_theme = active_route_theme.clone();
+        let active_policy = active_policy.clone();
+        let user_chosen = user_chosen.clone();
         #[cfg(target_arch = "wasm32")]
-        let preview_theme = preview_theme.clone();
+        let contrast = contrast.clone();
+        #[cfg(target_arch = "wasm32")]
+        let forced_colors = forced_colors.clone();
+        #[cfg(target_arch = "wasm32")]
+        let preferences = preferences.clone();
+        #[cfg(target_arch = "wasm32")]
+        let custom_themes = custom_themes_state.clone();
+        #[cfg(target_arch = "wasm32")]
+        let axis_values = axis_values.clone();
+        #[cfg(target_arch = "wasm32")]
+        let attribute = attribute.clone();
+        #[cfg(target_arch = "wasm32")]
+        let attribute_values = attribute_values.clone();
 
-        Callback::from(move |new_theme: Theme| {
+        Callback::from(move |(new_theme, reason): (Theme, ChangeReason)| {
             #[cfg(target_arch = "wasm32")]
-            {
+            let sys_theme = {
                 use crate::common::SYSTEM_THEME_QUERY;
 
                 let window = web_sys::window().unwrap();
@@ -122,67 +388,169 @@ pub fn theme_provider(props: &ThemeProviderProps) -> Html {
                     Theme::Light
                 };
                 system_theme.set(sys_theme.clone());
+                sys_theme
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            let sys_theme = Theme::Light;
+            #[cfg(target_arch = "wasm32")]
+            let hour = Some(web_sys::js_sys::Date::new_0().get_hours());
+            #[cfg(not(target_arch = "wasm32"))]
+            let hour = None;
 
-                let final_theme = if let Some(ref forced) = forced_theme {
-                    forced.clone()
-                } else if let Some(preview) = &*preview_theme {
-                    preview.clone()
+            let notify = |resolved: Theme| {
+                let previous = last_change.replace(Some((new_theme.clone(), resolved.clone())));
+                if let Some(change) =
+                    crate::change::since(previous, new_theme.clone(), resolved, reason)
+                {
+                    on_change.emit(change);
+                }
+            };
+            let policy = active_policy.borrow().clone();
+            let input = ResolveInput {
+                forced: forced_theme.clone(),
+                locked: policy.lock.clone(),
+                route: active_route_theme.borrow().clone(),
+                preview: active_preview
+                    .borrow()
+                    .clone()
+                    .filter(|preview| policy.permits(preview)),
+                choice: user_chosen
+                    .borrow()
+                    .then_some(new_theme.clone())
+                    .filter(|choice| policy.permits(choice)),
+                default: default_theme.clone(),
+                system: sys_theme,
+                parent: parent_theme.clone(),
+                hour,
+            };
+            let (final_theme, resolver) = input.resolve(&resolvers);
+            resolved_by.set(resolver);
+
+            #[cfg(target_arch = "wasm32")]
+            {
+                let current_contrast = crate::axis::contrast_override(&axis_values.borrow())
+                    .unwrap_or_else(crate::dom::current_contrast);
+                contrast.set(current_contrast);
+                let forced_colors_active = crate::dom::forced_colors_active();
+                forced_colors.set(forced_colors_active);
+                let current_preferences = crate::dom::current_preferences();
+                preferences.set(current_preferences);
+
+                // A theme inherited from the parent already has the contrast preference applied.
+                let final_theme = if parent_theme.as_ref() == Some(&final_theme) {
+                    final_theme
                 } else {
-                    match new_theme {
-                        Theme::System => sys_theme,
-                        other => other,
-                    }
+                    final_theme.with_contrast(
+                        current_contrast,
+                        &custom_themes,
+                        derive_contrast_themes,
+                    )
                 };
 
                 resolved_theme.set(final_theme.clone());
-                let _ = html_element.set_attribute("data-theme", &final_theme.as_str());
-                let _ = html_element.set_attribute("class", &final_theme.as_str());
-                let _ = html_element
-                    .set_attribute("style", &format!("color-scheme: {};", final_theme.as_str()));
+                notify(final_theme.clone());
+                let target = if scoped {
+                    scope_ref.cast::<web_sys::HtmlElement>()
+                } else {
+                    Some((*html_element).clone())
+                };
+                let Some(html_element) = target else {
+                    return;
+                };
+                let _transitions =
+                    crate::dom::disable_transitions(disable_transitions.then_some(transition_rule));
+                crate::dom::apply_theme(
+                    &html_element,
+                    &final_theme,
+                    &attribute,
+                    &attribute_values,
+                    &custom_themes,
+                );
+                let _ = html_element.set_attribute("data-contrast", current_contrast.as_str());
+                crate::dom::set_axis_attributes(&html_element, &axis_values.borrow());
+                for (name, value) in current_preferences.attributes() {
+                    let _ = html_element.set_attribute(name, value);
+                }
+                if emit_css_variables {
+                    let tokens = (!(forced_colors_active && respect_forced_colors)).then(|| {
+                        let tokens = apply_accent(
+                            final_theme.colors(Some(&custom_themes)),
+                            &axis_values.borrow(),
+                        );
+                        match &parent {
+                            Some(parent) => parent.colors().merge_with(&tokens),
+                            None => tokens,
+                        }
+                    });
+                    crate::dom::set_css_variables(&html_element, tokens.as_ref());
+                }
             }
 
             #[cfg(not(target_arch = "wasm32"))]
             {
-                resolved_theme.set(new_theme);
+                resolved_theme.set(final_theme.clone());
+                notify(final_theme);
             }
         })
     };
 
+    // Listeners registered once on mount go through these to see the latest render.
+    #[cfg(target_arch = "wasm32")]
+    let latest_update = use_mut_ref(|| update_resolved.clone());
+    let latest_theme = use_mut_ref(|| (*theme).clone());
+    *latest_theme.borrow_mut() = (*theme).clone();
+    #[cfg(target_arch = "wasm32")]
+    let latest_storage_name = use_mut_ref(|| storage_name);
+    #[cfg(target_arch = "wasm32")]
+    let latest_custom_themes = use_mut_ref(|| (*custom_themes_state).clone());
+    #[cfg(target_arch = "wasm32")]
+    {
+        *latest_update.borrow_mut() = update_resolved.clone();
+        *latest_storage_name.borrow_mut() = storage_name;
+        *latest_custom_themes.borrow_mut() = (*custom_themes_state).clone();
+    }
+
     {
         let update_resolved = update_resolved.clone();
         let theme_for_mq = theme.clone();
         #[cfg(target_arch = "wasm32")]
         let theme_for_storage = theme.clone();
         #[cfg(target_arch = "wasm32")]
-        let theme_for_interval = theme.clone();
+        let scheduled = resolvers
+            .iter()
+            .any(|resolver| matches!(resolver, ThemeResolver::Schedule { .. }));
+        #[cfg(target_arch = "wasm32")]
+        let user_chosen = user_chosen.clone();
+        #[cfg(target_arch = "wasm32")]
+        let latest_theme = latest_theme.clone();
 
         use_effect_with((), move |_| {
-            update_resolved.emit((*theme_for_mq).clone());
+            update_resolved.emit(((*theme_for_mq).clone(), ChangeReason::System));
 
             #[cfg(target_arch = "wasm32")]
             {
-                use crate::common::SYSTEM_THEME_QUERY;
-                use std::str::FromStr;
+                use crate::dom::{PREFERENCE_QUERIES, on_media_change};
                 use web_sys::wasm_bindgen::JsCast;
                 use web_sys::wasm_bindgen::closure::Closure;
 
                 let window = web_sys::window().unwrap();
 
-                let media_query = window.match_media(SYSTEM_THEME_QUERY).unwrap().unwrap();
-                let closure = Closure::wrap(Box::new({
-                    let update_resolved = update_resolved.clone();
-                    move |_event: web_sys::Event| {
-                        update_resolved.emit((*theme_for_mq).clone());
+                let refresh = {
+                    let latest_update = latest_update.clone();
+                    move |reason| {
+                        let update_resolved = latest_update.borrow().clone();
+                        update_resolved.emit((latest_theme.borrow().clone(), reason));
                     }
-                }) as Box<dyn FnMut(_)>);
-                media_query
-                    .add_event_listener_with_callback("change", closure.as_ref().unchecked_ref())
-                    .unwrap();
-                closure.forget();
+                };
+                for query in PREFERENCE_QUERIES {
+                    let refresh = refresh.clone();
+                    on_media_change(query, move || refresh(ChangeReason::System));
+                }
 
                 let storage_closure = Closure::wrap(Box::new({
                     let theme = theme_for_storage.clone();
-                    let update_resolved = update_resolved.clone();
+                    let latest_update = latest_update.clone();
+                    let user_chosen = user_chosen.clone();
                     move |_event: web_sys::StorageEvent| {
                         let window = web_sys::window().unwrap();
                         let storage = match storage_type {
@@ -190,52 +558,142 @@ pub fn theme_provider(props: &ThemeProviderProps) -> Html {
                             StorageType::SessionStorage => window.session_storage().unwrap(),
                         };
                         if let Some(storage) = storage {
+                            let storage_name = *latest_storage_name.borrow();
                             if let Ok(Some(value)) = storage.get_item(storage_name) {
-                                if let Ok(parsed) = Theme::from_str(&value) {
+                                let custom_themes = latest_custom_themes.borrow();
+                                if let Ok(parsed) = Theme::parse_with(&value, &*custom_themes) {
+                                    *user_chosen.borrow_mut() = true;
                                     theme.set(parsed.clone());
-                                    update_resolved.emit(parsed);
+                                    let update_resolved = latest_update.borrow().clone();
+                                    update_resolved.emit((parsed, ChangeReason::Storage));
                                 }
                             }
                         }
                     }
                 }) as Box<dyn FnMut(_)>);
-                window
-                    .add_event_listener_with_callback(
-                        "storage",
-                        storage_closure.as_ref().unchecked_ref(),
-                    )
-                    .unwrap();
-                storage_closure.forget();
-
-                let interval_closure = Closure::wrap(Box::new({
-                    let theme = theme_for_interval.clone();
-                    let update_resolved = update_resolved.clone();
-                    move || {
-                        let hour = web_sys::js_sys::Date::new_0().get_hours();
-                        let next = if (7..19).contains(&hour) {
-                            Theme::Light
-                        } else {
-                            Theme::Dark
-                        };
-                        theme.set(next.clone());
-                        update_resolved.emit(next);
-                    }
-                }) as Box<dyn FnMut()>);
-                let _id = window
-                    .set_interval_with_callback_and_timeout_and_arguments_0(
-                        interval_closure.as_ref().unchecked_ref(),
-                        60_000,
-                    )
-                    .unwrap();
-                interval_closure.forget();
+                if !scoped {
+                    window
+                        .add_event_listener_with_callback(
+                            "storage",
+                            storage_closure.as_ref().unchecked_ref(),
+                        )
+                        .unwrap();
+                    storage_closure.forget();
+                }
+
+                // A schedule is re-evaluated every minute.
+                if scheduled {
+                    let interval_closure =
+                        Closure::wrap(
+                            Box::new(move || refresh(ChangeReason::Schedule)) as Box<dyn FnMut()>
+                        );
+                    window
+                        .set_interval_with_callback_and_timeout_and_arguments_0(
+                            interval_closure.as_ref().unchecked_ref(),
+                            60_000,
+                        )
+                        .unwrap();
+                    interval_closure.forget();
+                }
             }
         });
     }
 
-    let set_theme = {
+    // A nested provider follows its parent when the parent resolves a new theme.
+    {
+        let update_resolved = update_resolved.clone();
+        let theme = theme.clone();
+        use_effect_with(parent_theme, move |parent_theme| {
+            if parent_theme.is_some() {
+                update_resolved.emit(((*theme).clone(), ChangeReason::System));
+            }
+        });
+    }
+
+    // Custom themes passed after mount are merged with those added through `set_custom_theme`.
+    {
+        let custom_themes_state = custom_themes_state.clone();
+        use_effect_with(custom_themes, move |custom_themes| {
+            let changed = custom_themes
+                .iter()
+                .any(|(name, theme)| custom_themes_state.get(name) != Some(theme));
+            if changed {
+                let mut themes = (*custom_themes_state).clone();
+                themes.extend(custom_themes.clone());
+                custom_themes_state.set(themes);
+            }
+        });
+    }
+
+    // Re-resolves when the forced theme or the custom themes change after mount.
+    {
+        let update_resolved = update_resolved.clone();
         let theme = theme.clone();
+        let mounted = use_mut_ref(|| false);
+        use_effect_with(forced_theme.clone(), move |_| {
+            if mounted.replace(true) {
+                update_resolved.emit(((*theme).clone(), ChangeReason::Forced));
+            }
+        });
+    }
+    {
         let update_resolved = update_resolved.clone();
-        Callback::from(move |new_theme: Theme| {
+        let theme = theme.clone();
+        let mounted = use_mut_ref(|| false);
+        use_effect_with(custom_themes_state.clone(), move |_| {
+            if mounted.replace(true) {
+                update_resolved.emit(((*theme).clone(), ChangeReason::CustomTheme));
+            }
+        });
+    }
+
+    // Moves the stored theme over when the storage key changes, and applies the theme stored
+    // under the new key.
+    #[cfg(target_arch = "wasm32")]
+    {
+        let theme = theme.clone();
+        let update_resolved = update_resolved.clone();
+        let custom_themes = custom_themes_state.clone();
+        let user_chosen = user_chosen.clone();
+        let applied_storage_name = use_mut_ref(|| storage_name);
+        use_effect_with(storage_name, move |&storage_name| {
+            let previous = applied_storage_name.replace(storage_name);
+            if previous == storage_name {
+                return;
+            }
+            let Some(storage) = crate::dom::storage(storage_type).filter(|_| !scoped) else {
+                return;
+            };
+            crate::dom::migrate_item(&storage, previous, storage_name);
+            let stored = storage
+                .get_item(storage_name)
+                .ok()
+                .flatten()
+                .and_then(|value| Theme::parse_with(&value, &*custom_themes).ok());
+            if let Some(stored) = stored {
+                *user_chosen.borrow_mut() = true;
+                theme.set(stored.clone());
+                update_resolved.emit((stored, ChangeReason::Storage));
+            }
+        });
+    }
+
+    let change_theme = {
+        let theme = theme.clone();
+        let latest_theme = latest_theme.clone();
+        let update_resolved = update_resolved.clone();
+        let preview_theme = preview_theme.clone();
+        let active_preview = active_preview.clone();
+        let user_chosen = user_chosen.clone();
+        let active_policy = active_policy.clone();
+        Callback::from(move |(new_theme, origin): (Theme, Option<(f64, f64)>)| {
+            active_policy.borrow().check(&new_theme)?;
+            *user_chosen.borrow_mut() = true;
+            // Selecting a theme ends any preview.
+            if active_preview.borrow_mut().take().is_some() {
+                preview_theme.set(None);
+            }
+
             #[cfg(target_arch = "wasm32")]
             {
                 let window = web_sys::window().unwrap();
@@ -243,16 +701,95 @@ pub fn theme_provider(props: &ThemeProviderProps) -> Html {
                     StorageType::LocalStorage => window.local_storage().unwrap(),
                     StorageType::SessionStorage => window.session_storage().unwrap(),
                 };
-                if let Some(storage) = storage {
-                    let _ = storage.set_item(storage_name, &new_theme.as_str());
+                if let Some(storage) = storage.filter(|_| !scoped) {
+                    let _ = storage.set_item(storage_name, &new_theme.to_string());
                 }
             }
 
-            theme.set(new_theme.clone());
-            update_resolved.emit(new_theme);
+            let theme = theme.clone();
+            let latest_theme = latest_theme.clone();
+            let update_resolved = update_resolved.clone();
+            let update = move || {
+                *latest_theme.borrow_mut() = new_theme.clone();
+                theme.set(new_theme.clone());
+                update_resolved.emit((new_theme, ChangeReason::User));
+            };
+            #[cfg(target_arch = "wasm32")]
+            crate::dom::start_view_transition(transition, origin, update);
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let _ = origin;
+                update();
+            }
+            Ok(())
+        })
+    };
+    let record_history = {
+        let history = history.clone();
+        let active_history = active_history.clone();
+        Callback::from(move |entry: HistoryEntry| {
+            active_history.borrow_mut().record(entry);
+            history.set(active_history.borrow().clone());
+        })
+    };
+    // Changes the theme like `change_theme`, recording the change in the history.
+    let select_theme = {
+        let theme = theme.clone();
+        let change_theme = change_theme.clone();
+        let record_history = record_history.clone();
+        Callback::from(move |(new_theme, origin): (Theme, Option<(f64, f64)>)| {
+            let previous = (*theme).clone();
+            change_theme.emit((new_theme.clone(), origin))?;
+            if previous != new_theme {
+                record_history.emit(HistoryEntry::Theme {
+                    previous,
+                    next: new_theme,
+                });
+            }
+            Ok(())
+        })
+    };
+    let try_set_theme = select_theme.reform(|new_theme| (new_theme, None));
+    let set_theme = {
+        let try_set_theme = try_set_theme.clone();
+        Callback::from(move |new_theme| report_rejection(try_set_theme.emit(new_theme)))
+    };
+    let set_theme_at = {
+        let select_theme = select_theme.clone();
+        Callback::from(move |(new_theme, origin)| {
+            report_rejection(select_theme.emit((new_theme, Some(origin))))
         })
     };
 
+    let set_policy = {
+        let theme = theme.clone();
+        let update_resolved = update_resolved.clone();
+        let policy_state = policy_state.clone();
+        let active_policy = active_policy.clone();
+        Callback::from(move |next: ThemePolicy| {
+            if *active_policy.borrow() != next {
+                *active_policy.borrow_mut() = next.clone();
+                policy_state.set(next);
+                update_resolved.emit(((*theme).clone(), ChangeReason::Policy));
+            }
+        })
+    };
+    let set_lock = {
+        let set_policy = set_policy.clone();
+        let active_policy = active_policy.clone();
+        Callback::from(move |lock: Option<Theme>| {
+            let next = ThemePolicy {
+                lock,
+                ..active_policy.borrow().clone()
+            };
+            set_policy.emit(next);
+        })
+    };
+    {
+        let set_policy = set_policy.clone();
+        use_effect_with(policy, move |policy| set_policy.emit(policy.clone()));
+    }
+
     let set_custom_theme = {
         let custom_themes_state = custom_themes_state.clone();
         Callback::from(move |new_custom_theme: Rc<CustomTheme>| {
@@ -262,11 +799,83 @@ pub fn theme_provider(props: &ThemeProviderProps) -> Html {
                 return;
             }
             let mut themes = (*custom_themes_state).clone();
-            themes.insert(new_custom_theme.name.clone(), new_custom_theme.clone());
+            let previous = themes.insert(new_custom_theme.name.clone(), new_custom_theme.clone());
             custom_themes_state.set(themes);
+            if previous.as_ref() != Some(&new_custom_theme) {
+                record_history.emit(HistoryEntry::CustomTheme {
+                    previous,
+                    next: new_custom_theme,
+                });
+            }
+        })
+    };
+
+    // Applies a recorded change again, or reverts it when `undo` is set.
+    let apply_history_entry = {
+        let custom_themes_state = custom_themes_state.clone();
+        Callback::from(
+            move |(entry, undo): (HistoryEntry, bool)| -> Result<(), ThemeError> {
+                match entry {
+                    HistoryEntry::Theme { previous, next } => {
+                        change_theme.emit((if undo { previous } else { next }, None))
+                    }
+                    HistoryEntry::CustomTheme { previous, next } => {
+                        let mut themes = (*custom_themes_state).clone();
+                        match previous.filter(|_| undo) {
+                            Some(previous) => themes.insert(previous.name.clone(), previous),
+                            None if undo => themes.remove(&next.name),
+                            None => themes.insert(next.name.clone(), next),
+                        };
+                        custom_themes_state.set(themes);
+                        Ok(())
+                    }
+                }
+            },
+        )
+    };
+    let undo = {
+        let history = history.clone();
+        let active_history = active_history.clone();
+        let apply_history_entry = apply_history_entry.clone();
+        Callback::from(move |_| {
+            let entry = active_history.borrow_mut().undo();
+            if let Some(entry) = entry {
+                if let Err(error) = apply_history_entry.emit((entry, true)) {
+                    active_history.borrow_mut().redo();
+                    report_rejection(Err(error));
+                }
+                history.set(active_history.borrow().clone());
+            }
+        })
+    };
+    let redo = {
+        let history = history.clone();
+        let active_history = active_history.clone();
+        Callback::from(move |_| {
+            let entry = active_history.borrow_mut().redo();
+            if let Some(entry) = entry {
+                if let Err(error) = apply_history_entry.emit((entry, false)) {
+                    active_history.borrow_mut().undo();
+                    report_rejection(Err(error));
+                }
+                history.set(active_history.borrow().clone());
+            }
         })
     };
 
+    // Persists the history next to the theme.
+    #[cfg(target_arch = "wasm32")]
+    {
+        use_effect_with((*history).clone(), move |history| {
+            if let Some(storage) =
+                crate::dom::storage(storage_type).filter(|_| persist_history && !scoped)
+            {
+                let _ =
+                    storage.set_item(&ThemeHistory::storage_key(storage_name), &history.encode());
+            }
+        });
+    }
+
     let reset_to_system = {
         let set_theme = set_theme.clone();
         Callback::from(move |_| {
@@ -274,43 +883,347 @@ pub fn theme_provider(props: &ThemeProviderProps) -> Html {
         })
     };
 
+    let cancel_preview = {
+        let theme = theme.clone();
+        let update_resolved = update_resolved.clone();
+        let preview_theme = preview_theme.clone();
+        let active_preview = active_preview.clone();
+        Callback::from(move |_| {
+            if active_preview.borrow_mut().take().is_some() {
+                preview_theme.set(None);
+                update_resolved.emit(((*theme).clone(), ChangeReason::Preview));
+            }
+        })
+    };
+
+    let commit_preview = {
+        let set_theme = set_theme.clone();
+        let active_preview = active_preview.clone();
+        Callback::from(move |_| {
+            let preview = active_preview.borrow().clone();
+            if let Some(preview) = preview {
+                set_theme.emit(preview);
+            }
+        })
+    };
+
+    let set_route_theme = {
+        let latest_theme = latest_theme.clone();
+        let update_resolved = update_resolved.clone();
+        let route_theme = route_theme.clone();
+        Callback::from(move |next: RouteTheme| {
+            if *active_route_theme.borrow() != next {
+                *active_route_theme.borrow_mut() = next.clone();
+                route_theme.set(next);
+                // The latest selection, as route components hold on to this from earlier renders.
+                let theme = latest_theme.borrow().clone();
+                update_resolved.emit((theme, ChangeReason::Route));
+            }
+        })
+    };
+
+    // Counts previews, so an auto-revert only cancels the preview that scheduled it.
+    let preview_generation = use_mut_ref(|| 0u32);
+
     let apply_preview = {
+        let theme = theme.clone();
+        let update_resolved = update_resolved.clone();
         let preview_theme = preview_theme.clone();
         #[cfg(target_arch = "wasm32")]
-        let html_element = html_element.clone();
-        Callback::from(move |theme: Theme| {
-            preview_theme.set(Some(theme.clone()));
+        let cancel_preview = cancel_preview.clone();
+        Callback::from(move |preview: Theme| {
+            *active_preview.borrow_mut() = Some(preview.clone());
+            preview_theme.set(Some(preview));
+            update_resolved.emit(((*theme).clone(), ChangeReason::Preview));
+
+            *preview_generation.borrow_mut() += 1;
             #[cfg(target_arch = "wasm32")]
-            {
-                let _ = html_element.set_attribute("data-theme", &theme.as_str());
-                let _ = html_element.set_attribute("class", &theme.as_str());
-                let _ = html_element
-                    .set_attribute("style", &format!("color-scheme: {};", theme.as_str()));
+            if let Some(timeout_ms) = preview_timeout_ms {
+                let generation = *preview_generation.borrow();
+                let preview_generation = preview_generation.clone();
+                let cancel_preview = cancel_preview.clone();
+                crate::dom::set_timeout(timeout_ms, move || {
+                    if *preview_generation.borrow() == generation {
+                        cancel_preview.emit(());
+                    }
+                });
             }
         })
     };
 
+    let set_axis = {
+        let axis_values = axis_values.clone();
+        let axes_state = axes_state.clone();
+        let theme = theme.clone();
+        let update_resolved = update_resolved.clone();
+        Callback::from(move |(name, value): (String, String)| {
+            let Some(axis) = axes.iter().find(|axis| axis.name == name) else {
+                return;
+            };
+            if axis.forced.is_some() || !axis.accepts(&value) {
+                return;
+            }
+            #[cfg(target_arch = "wasm32")]
+            if let Some(storage) = crate::dom::storage(storage_type).filter(|_| !scoped) {
+                let _ = storage.set_item(&axis.storage_key(), &value);
+            }
+            axis_values.borrow_mut().insert(name, value);
+            axes_state.set(axis_values.borrow().clone());
+            update_resolved.emit(((*theme).clone(), ChangeReason::User));
+        })
+    };
+
+    let can_undo = history.can_undo();
+    let can_redo = history.can_redo();
     let context = Rc::new(ThemeContext {
         theme,
         resolved_theme,
         system_theme,
+        contrast,
+        forced_colors,
+        preferences,
         set_theme,
+        set_theme_at,
+        try_set_theme,
         forced_theme,
         custom_themes: custom_themes_state,
         set_custom_theme,
         reset_to_system,
         preview_theme,
         apply_preview,
+        cancel_preview,
+        commit_preview,
+        axes: axes_state,
+        set_axis,
+        route_theme,
+        set_route_theme,
+        resolved_by,
+        policy: policy_state,
+        set_policy,
+        set_lock,
+        undo,
+        redo,
+        can_undo,
+        can_redo,
+        parent,
     });
 
+    // The root provider keeps the global store in sync with its selection, in both directions.
+    use_effect_with((*context.theme).clone(), move |theme| {
+        if is_root {
+            set_global_theme(theme.clone());
+        }
+    });
+    {
+        let latest_try_set_theme = use_mut_ref(|| context.try_set_theme.clone());
+        *latest_try_set_theme.borrow_mut() = context.try_set_theme.clone();
+        use_effect_with((), move |_| {
+            let subscription = is_root.then(|| {
+                subscribe_theme(Callback::from(move |next: Theme| {
+                    if *latest_theme.borrow() == next {
+                        return;
+                    }
+                    let try_set_theme = latest_try_set_theme.borrow().clone();
+                    if let Err(error) = try_set_theme.emit(next) {
+                        report_rejection(Err(error));
+                        // Keep the store on the theme the provider still shows.
+                        set_global_theme(latest_theme.borrow().clone());
+                    }
+                }))
+            });
+            move || drop(subscription)
+        });
+    }
+    {
+        let context = context.clone();
+        use_effect(move || {
+            if is_root {
+                GLOBAL_CONTEXT.with(|global| *global.borrow_mut() = Some(context));
+            }
+            move || {
+                if is_root {
+                    GLOBAL_CONTEXT.with(|global| *global.borrow_mut() = None);
+                }
+            }
+        });
+    }
+
     html! {
         <ContextProvider<Rc<ThemeContext>> context={context}>
-            { for children.iter() }
+            if scoped {
+                <div ref={scope_ref} data-theme-scope="">
+                    { for children.iter() }
+                </div>
+            } else {
+                { for children.iter() }
+            }
         </ContextProvider<Rc<ThemeContext>>>
     }
 }
 
+/// Logs a theme selection rejected by the `ThemePolicy`.
+fn report_rejection(result: Result<(), ThemeError>) {
+    #[cfg(target_arch = "wasm32")]
+    if let Err(error) = result {
+        web_sys::console::error_1(&format!("Theme policy error: {}", error).into());
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = result;
+}
+
 #[hook]
 pub fn use_theme() -> Rc<ThemeContext> {
     use_context::<Rc<ThemeContext>>().expect("No ThemeProvider found")
 }
+
+/// Applies the theme `route` asks for while the calling component is mounted.
+///
+/// Call it from a component below both the router and the `ThemeProvider` with the current
+/// route, e.g. `use_route_theme(use_route::<Route>())`. The user's choice is used again once
+/// a route no longer overrides it.
+#[hook]
+pub fn use_route_theme<R>(route: R)
+where
+    R: ThemedRoute,
+{
+    let ctx = use_theme();
+    // The cleanup on unmount must see the callback of the latest render.
+    let latest_set_route_theme = use_mut_ref(|| ctx.set_route_theme.clone());
+    *latest_set_route_theme.borrow_mut() = ctx.set_route_theme.clone();
+    {
+        let set_route_theme = ctx.set_route_theme.clone();
+        use_effect_with(route.route_theme(), move |route_theme| {
+            set_route_theme.emit(route_theme.clone());
+        });
+    }
+    use_effect_with((), move |_| {
+        move || {
+            let set_route_theme = latest_set_route_theme.borrow().clone();
+            set_route_theme.emit(RouteTheme::Inherit);
+        }
+    });
+}
+
+/// Returns the context of the nearest `ThemeProvider`, or `None` outside of one.
+#[hook]
+pub fn use_try_theme() -> Option<Rc<ThemeContext>> {
+    use_context::<Rc<ThemeContext>>()
+}
+
+thread_local! {
+    /// The selected theme, shared with code outside the component tree.
+    static GLOBAL_THEME: RefCell<Option<Theme>> = const { RefCell::new(None) };
+    /// Context of the mounted root provider.
+    static GLOBAL_CONTEXT: RefCell<Option<Rc<ThemeContext>>> = const { RefCell::new(None) };
+    static SUBSCRIBERS: RefCell<Vec<(usize, Callback<Theme>)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_SUBSCRIBER_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Returns the theme selected in the global store, for code outside the component tree such
+/// as router guards or logging.
+///
+/// The root `ThemeProvider`, the outermost one that is not scoped, publishes its selection
+/// here. Returns `None` until a theme was selected or a root provider mounted.
+pub fn global_theme() -> Option<Theme> {
+    GLOBAL_THEME.with(|global| global.borrow().clone())
+}
+
+/// Selects `theme` in the global store and notifies its subscribers.
+///
+/// Works without a provider mounted. A root provider mounted later starts from this theme,
+/// and a mounted one applies it as if `set_theme` was called, subject to its policy.
+pub fn set_global_theme(theme: Theme) {
+    let changed = GLOBAL_THEME.with(|global| {
+        let mut global = global.borrow_mut();
+        if global.as_ref() == Some(&theme) {
+            return false;
+        }
+        *global = Some(theme.clone());
+        true
+    });
+    if !changed {
+        return;
+    }
+    // Collected first, so subscribers may subscribe, unsubscribe or select while being notified.
+    let subscribers: Vec<_> = SUBSCRIBERS.with(|subscribers| {
+        subscribers
+            .borrow()
+            .iter()
+            .map(|(_, callback)| callback.clone())
+            .collect()
+    });
+    for callback in subscribers {
+        callback.emit(theme.clone());
+    }
+}
+
+/// Calls `callback` with every theme selected in the global store, until the returned
+/// [`ThemeSubscription`] is dropped.
+pub fn subscribe_theme(callback: Callback<Theme>) -> ThemeSubscription {
+    let id = NEXT_SUBSCRIBER_ID.with(|next| next.replace(next.get() + 1));
+    SUBSCRIBERS.with(|subscribers| subscribers.borrow_mut().push((id, callback)));
+    ThemeSubscription(id)
+}
+
+/// Keeps a callback registered with [`subscribe_theme`]; dropping it unsubscribes.
+#[must_use = "dropping the subscription unsubscribes immediately"]
+pub struct ThemeSubscription(usize);
+
+impl Drop for ThemeSubscription {
+    fn drop(&mut self) {
+        SUBSCRIBERS.with(|subscribers| subscribers.borrow_mut().retain(|(id, _)| *id != self.0));
+    }
+}
+
+/// Returns the context of the mounted root `ThemeProvider`, or `None` while none is mounted.
+pub fn global_context() -> Option<Rc<ThemeContext>> {
+    GLOBAL_CONTEXT.with(|global| global.borrow().clone())
+}
+
+/// Returns the theme selected in the global store, re-rendering the calling component when it
+/// changes. Works outside of any `ThemeProvider`.
+#[hook]
+pub fn use_global_theme() -> Option<Theme> {
+    let theme = use_state(global_theme);
+    {
+        let theme = theme.clone();
+        use_effect_with((), move |_| {
+            let subscription = subscribe_theme(Callback::from(move |next| theme.set(Some(next))));
+            move || drop(subscription)
+        });
+    }
+    (*theme).clone()
+}
+
+/// Returns the tokens of [`ThemeContext::colors`], recomputed only when the resolved theme,
+/// the custom themes, the axes or the forced colors mode change.
+#[hook]
+pub fn use_theme_tokens() -> Rc<ColorTokens> {
+    let ctx = use_theme();
+    use_memo(ctx.color_inputs(), |_| ctx.colors())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_store_works_without_a_provider() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let subscription = {
+            let seen = seen.clone();
+            subscribe_theme(Callback::from(move |theme| seen.borrow_mut().push(theme)))
+        };
+
+        assert_eq!(global_theme(), None);
+        set_global_theme(Theme::Dark);
+        set_global_theme(Theme::Dark);
+        assert_eq!(global_theme(), Some(Theme::Dark));
+        assert!(global_context().is_none());
+
+        drop(subscription);
+        set_global_theme(Theme::Light);
+        assert_eq!(global_theme(), Some(Theme::Light));
+        assert_eq!(*seen.borrow(), vec![Theme::Dark]);
+    }
+}
@@ -0,0 +1,192 @@
+//! Prebuilt, accessible theme switchers. Each one must be rendered inside a [`ThemeProvider`].
+//!
+//! [`ThemeProvider`]: super::ThemeProvider
+
+use super::use_theme;
+use crate::common::{Theme, step_theme, theme_options};
+use web_sys::HtmlElement;
+use web_sys::wasm_bindgen::JsCast;
+use yew::prelude::*;
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ThemeToggleProps {
+    #[prop_or_default]
+    pub class: Classes,
+    #[prop_or_default]
+    pub button_class: Classes,
+    #[prop_or_default]
+    pub active_class: Classes,
+    #[prop_or(AttrValue::Static("Theme"))]
+    pub aria_label: AttrValue,
+}
+
+/// A light, dark and system radio group, navigable with the arrow keys.
+#[function_component(ThemeToggle)]
+pub fn theme_toggle(props: &ThemeToggleProps) -> Html {
+    let ThemeToggleProps {
+        class,
+        button_class,
+        active_class,
+        aria_label,
+    } = props.clone();
+    let ctx = use_theme();
+    let options = [Theme::Light, Theme::Dark, Theme::System];
+    let selected = options.iter().position(|option| *option == *ctx.theme);
+
+    let onkeydown = {
+        let ctx = ctx.clone();
+        let options = options.clone();
+        Callback::from(move |event: KeyboardEvent| {
+            let forward = match event.key().as_str() {
+                "ArrowRight" | "ArrowDown" => true,
+                "ArrowLeft" | "ArrowUp" => false,
+                _ => return,
+            };
+            event.prevent_default();
+            let next = step_theme(&options, &ctx.theme, forward);
+            focus_option(&event, &next);
+            ctx.set_theme.emit(next);
+        })
+    };
+
+    html! {
+        <div role="radiogroup" aria-label={aria_label} {class} {onkeydown}>
+            { for options.iter().enumerate().map(|(index, option)| {
+                let checked = selected == Some(index);
+                let focusable = selected.unwrap_or(0) == index;
+                let onclick = {
+                    let set_theme = ctx.set_theme.clone();
+                    let option = option.clone();
+                    Callback::from(move |_: MouseEvent| set_theme.emit(option.clone()))
+                };
+                html! {
+                    <button
+                        type="button"
+                        role="radio"
+                        aria-checked={checked.to_string()}
+                        tabindex={if focusable { "0" } else { "-1" }}
+                        data-theme-option={option.to_string()}
+                        class={classes!(button_class.clone(), checked.then(|| active_class.clone()))}
+                        {onclick}
+                    >
+                        { option.label() }
+                    </button>
+                }
+            }) }
+        </div>
+    }
+}
+
+/// Moves focus to the option of `theme` in the group handling `event`.
+fn focus_option(event: &KeyboardEvent, theme: &Theme) {
+    let option = event
+        .current_target()
+        .and_then(|group| group.dyn_into::<web_sys::Element>().ok())
+        .and_then(|group| {
+            let selector = format!("[data-theme-option=\"{}\"]", theme);
+            group.query_selector(&selector).ok().flatten()
+        })
+        .and_then(|option| option.dyn_into::<HtmlElement>().ok());
+    if let Some(option) = option {
+        let _ = option.focus();
+    }
+}
+
+fn default_cycle() -> Vec<Theme> {
+    vec![Theme::Light, Theme::Dark, Theme::System]
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ThemeCycleButtonProps {
+    #[prop_or_default]
+    pub class: Classes,
+    #[prop_or_else(default_cycle)]
+    pub themes: Vec<Theme>,
+}
+
+/// A button stepping through `themes`, light, dark and system by default.
+///
+/// The current theme is exposed as `data-theme-current` for styling, e.g. to show an icon.
+/// Clicks start the switch from the pointer, for a [`ThemeTransition::CircularReveal`].
+///
+/// [`ThemeTransition::CircularReveal`]: crate::ThemeTransition::CircularReveal
+#[function_component(ThemeCycleButton)]
+pub fn theme_cycle_button(props: &ThemeCycleButtonProps) -> Html {
+    let ThemeCycleButtonProps { class, themes } = props.clone();
+    let ctx = use_theme();
+    let current = (*ctx.theme).clone();
+    let next = step_theme(&themes, &current, true);
+    let aria_label = format!("Theme: {}. Switch to {}.", current.label(), next.label());
+
+    let onclick = {
+        let ctx = ctx.clone();
+        let next = next.clone();
+        Callback::from(move |event: MouseEvent| {
+            // Keyboard activation reports no pointer position.
+            if event.detail() == 0 {
+                ctx.set_theme.emit(next.clone());
+            } else {
+                let origin = (event.client_x() as f64, event.client_y() as f64);
+                ctx.set_theme_at.emit((next.clone(), origin));
+            }
+        })
+    };
+
+    html! {
+        <button
+            type="button"
+            aria-label={aria_label}
+            data-theme-current={current.to_string()}
+            {class}
+            {onclick}
+        >
+            { current.label() }
+        </button>
+    }
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ThemeSelectProps {
+    #[prop_or_default]
+    pub class: Classes,
+    #[prop_or(AttrValue::Static("Theme"))]
+    pub aria_label: AttrValue,
+    #[prop_or(true)]
+    pub include_system: bool,
+}
+
+/// A `<select>` listing the built-in themes and every custom theme of the provider.
+#[function_component(ThemeSelect)]
+pub fn theme_select(props: &ThemeSelectProps) -> Html {
+    let ThemeSelectProps {
+        class,
+        aria_label,
+        include_system,
+    } = props.clone();
+    let ctx = use_theme();
+    let current = ctx.theme.to_string();
+
+    let onchange = {
+        let ctx = ctx.clone();
+        Callback::from(move |event: Event| {
+            let value = event
+                .target_unchecked_into::<web_sys::HtmlSelectElement>()
+                .value();
+            if let Ok(theme) = Theme::parse_with(&value, &*ctx.custom_themes) {
+                ctx.set_theme.emit(theme);
+            }
+        })
+    };
+
+    html! {
+        <select aria-label={aria_label} {class} {onchange}>
+            { for theme_options(&ctx.custom_themes, include_system).into_iter().map(|option| {
+                let value = option.to_string();
+                let selected = value == current;
+                html! {
+                    <option {selected} {value}>{ option.label() }</option>
+                }
+            }) }
+        </select>
+    }
+}